@@ -0,0 +1,197 @@
+//! Interactive mode of the program (`--interactive`), where the grid is filled in by hand in the terminal with the help of the solver.
+
+use std::io::{Stdout, Write};
+
+use crossterm::{cursor, execute, queue, terminal};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::{Print, PrintStyledContent, Stylize};
+use sudoku_solver::{SudokuGrid, cell_ref, solve};
+
+/// Keys of the interactive mode, shown under the grid.
+const HELP: &str = "Arrows: move, 1-9: place, 0/Del: clear, c: candidates, h: hint, s: solve, u/r: undo/redo, q: quit";
+
+/// State of an interactive session.
+struct Session {
+    /// Grid being filled in, whose moves are tracked so that they can be undone.
+    grid: SudokuGrid,
+    /// Whether each cell (in row-major order) holds a digit of the puzzle, which can't be edited.
+    given: Vec<bool>,
+    /// Column and row of the selected cell.
+    cursor: (usize, usize),
+    /// Whether the candidates of the empty cells are drawn.
+    show_candidates: bool,
+    /// Message shown under the grid, such as the last hint.
+    message: String,
+    /// Maximum number of iterations of the solver when the grid is solved from the current state.
+    max_iterations: u32
+}
+
+/// Puts the terminal in raw mode on an alternate screen, and restores it when dropped, even if the session panics.
+struct RawTerminal(Stdout);
+
+impl RawTerminal {
+    fn enter() -> std::io::Result<RawTerminal> {
+        let mut stdout = std::io::stdout();
+        terminal::enable_raw_mode()?;
+        execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(RawTerminal(stdout))
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = execute!(self.0, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+impl Session {
+    fn new(grid: SudokuGrid, max_iterations: u32) -> Session {
+        let given = (0..81).map(|i| grid.get(i % 9, i / 9) != 0).collect();
+        Session {
+            grid,
+            given,
+            cursor: (0, 0),
+            show_candidates: false,
+            message: String::new(),
+            max_iterations
+        }
+    }
+
+    /// Places a digit in the selected cell, or empties it with 0. The digits of the puzzle are left as they are.
+    fn place(&mut self, value: u8) {
+        let (x, y) = self.cursor;
+        if self.given[y * 9 + x] {
+            self.message = format!("{} is given by the puzzle.", cell_ref(x, y));
+            return
+        }
+        self.grid.set_tracked(x, y, value);
+        self.message = match self.grid.is_solved() {
+            true => String::from("Solved! Press q to quit."),
+            false => String::new()
+        };
+    }
+
+    /// Handles a key press, returning false when the session is over.
+    fn handle(&mut self, code: KeyCode) -> bool {
+        let (x, y) = self.cursor;
+        match code {
+            KeyCode::Left => self.cursor = ((x + 8) % 9, y),
+            KeyCode::Right => self.cursor = ((x + 1) % 9, y),
+            KeyCode::Up => self.cursor = (x, (y + 8) % 9),
+            KeyCode::Down => self.cursor = (x, (y + 1) % 9),
+            KeyCode::Char(c @ '1'..='9') => self.place(c as u8 - b'0'),
+            KeyCode::Char('0' | '.' | ' ') | KeyCode::Backspace | KeyCode::Delete => self.place(0),
+            KeyCode::Char('c') => self.show_candidates = !self.show_candidates,
+            KeyCode::Char('h') => self.message = match self.grid.hint() {
+                Some(hint) => {
+                    self.cursor = (hint.x, hint.y);
+                    format!("Hint: {}.", hint)
+                },
+                None => String::from("No logical move was found, a digit may be wrong.")
+            },
+            KeyCode::Char('s') => self.message = match solve(self.grid.clone(), self.max_iterations) {
+                Ok(solved) => {
+                    // Every digit is placed as a move, so that the solving can be undone
+                    for i in 0..81 {
+                        self.grid.set_tracked(i % 9, i / 9, solved.get(i % 9, i / 9))
+                    }
+                    String::from("Solved! Press q to quit.")
+                },
+                Err(err) => format!("The grid can't be solved from here: {}", err)
+            },
+            KeyCode::Char('u') if !self.grid.undo() => self.message = String::from("Nothing to undo."),
+            KeyCode::Char('r') if !self.grid.redo() => self.message = String::from("Nothing to redo."),
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            _ => {}
+        }
+        true
+    }
+
+    /// Draws the grid, with the selected cell highlighted, the digits of the puzzle in bold and the repeated digits in red.
+    /// With the candidates shown, every cell takes 3 lines of 3 characters, where each candidate has its own spot.
+    fn draw(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let cell_size = if self.show_candidates { 3 } else { 1 };
+        let separator = format!("+{}", format!("{}+", "-".repeat(3 * (cell_size + 1) + 1)).repeat(3));
+        let conflicts = self.grid.conflicts();
+        let mut row = 0;
+
+        queue!(out, terminal::Clear(terminal::ClearType::All))?;
+        for y in 0..9 {
+            if y % 3 == 0 {
+                next_line(out, &mut row)?;
+                queue!(out, Print(&separator))?;
+            }
+            for sub_line in 0..cell_size {
+                next_line(out, &mut row)?;
+                for x in 0..9 {
+                    if x % 3 == 0 {
+                        queue!(out, Print("| "))?;
+                    }
+                    let value = self.grid.get(x, y);
+                    let text = match value {
+                        0 if self.show_candidates => {
+                            let candidates = self.grid.candidates(x, y);
+                            (1..=3).map(|i| 3 * sub_line as u8 + i).map(|digit| if candidates.contains(&digit) { (b'0' + digit) as char } else { ' ' }).collect()
+                        },
+                        0 => String::from("."),
+                        value if self.show_candidates => if sub_line == 1 { format!(" {} ", value) } else { String::from("   ") },
+                        value => value.to_string()
+                    };
+
+                    let mut styled = text.stylize();
+                    if self.given[y * 9 + x] {
+                        styled = styled.bold()
+                    } else if value == 0 {
+                        styled = styled.dark_grey()
+                    }
+                    if conflicts.contains(&(x, y)) {
+                        styled = styled.red()
+                    }
+                    if self.cursor == (x, y) {
+                        styled = styled.reverse()
+                    }
+                    queue!(out, PrintStyledContent(styled), Print(" "))?;
+                }
+                queue!(out, Print("|"))?;
+            }
+        }
+        next_line(out, &mut row)?;
+        queue!(out, Print(&separator))?;
+
+        let (x, y) = self.cursor;
+        next_line(out, &mut row)?;
+        queue!(out, Print(format!("{}  {}", cell_ref(x, y), self.message)))?;
+        next_line(out, &mut row)?;
+        queue!(out, Print(HELP))?;
+        out.flush()
+    }
+}
+
+/// Moves the cursor of the terminal to the start of a line, since raw mode doesn't handle line breaks, and counts the line.
+fn next_line(out: &mut impl Write, row: &mut u16) -> std::io::Result<()> {
+    queue!(out, cursor::MoveTo(0, *row))?;
+    *row += 1;
+    Ok(())
+}
+
+/// Runs an interactive session on a 9x9 grid, until the user quits, and returns the grid as it was left.
+/// The digits of the grid can't be edited, the others are placed by the user, and the solver is used to give hints or to finish the grid.
+pub fn run(grid: SudokuGrid, max_iterations: u32) -> std::io::Result<SudokuGrid> {
+    let mut screen = RawTerminal::enter()?;
+    let mut session = Session::new(grid, max_iterations);
+
+    loop {
+        session.draw(&mut screen.0)?;
+        if let Event::Key(key) = event::read()? {
+            // Releases are reported on some platforms, but only presses make moves
+            if key.kind != KeyEventKind::Press {
+                continue
+            }
+            if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) || !session.handle(key.code) {
+                break
+            }
+        }
+    }
+    Ok(session.grid)
+}
@@ -0,0 +1,3824 @@
+//! Solver for 9x9 Sudoku puzzles, with support for the larger 16x16 grids of hexadoku.
+//!
+//! Cells are addressed with (x, y) coordinates, where x is the column and y the row, both starting at 0 from the top left corner.
+
+use std::convert::Infallible;
+use std::fmt::{Debug, Display, Formatter};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::time::{Duration, Instant};
+
+// Four libraries are used:
+// - rand for random number generation
+// - regex for regex matching in input strings
+// - serde_json for JSON input and output (serde itself is only used by the optional `serde` feature)
+// - flate2 for reading gzip-compressed puzzle files
+// The optional `rayon` feature also brings rayon, to count solutions on several threads, and the `wasm` feature brings wasm-bindgen for the `wasm` module.
+use rand::{Rng, SeedableRng, thread_rng};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use regex::Regex;
+use serde_json::json;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod recognition;
+
+#[cfg(test)]
+mod tests;
+
+/// Enum of the rules a grid follows, on top of the unique digits of every row, column and group.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GridKind {
+    /// The classic rules.
+    Classic,
+    /// X-Sudoku: the two main diagonals must also hold every digit once.
+    Diagonal,
+    /// Anti-Knight Sudoku: two cells a chess knight's move apart can't hold the same digit.
+    AntiKnight
+}
+
+/// Offsets of the moves of a chess knight, used by Anti-Knight Sudoku.
+const KNIGHT_MOVES: [(isize, isize); 8] = [(1, -2), (2, -1), (2, 1), (1, 2), (-1, 2), (-2, 1), (-2, -1), (-1, -2)];
+
+/// Structure that represents a Sudoku grid (9*9 by default, or any size*size grid whose size is a square)
+pub struct SudokuGrid {
+    /// size must be `size * size`
+    data: Box<[u8]>,
+    /// Length of a side of the grid, which is also the amount of digits.
+    size: usize,
+    /// Length of a side of a group, the square root of `size`.
+    box_size: usize,
+    /// Rules the grid follows.
+    kind: GridKind,
+    /// Cages of a Killer Sudoku, empty for any other grid.
+    cages: Box<[Cage]>,
+    /// Candidates each cell of a 9x9 grid is restricted to by the pencil marks of `from_candidates()`, in the convention of `candidates_mask()`, if there is such a restriction.
+    /// It is boxed to keep the grid small, since it is moved around in every result of the solver.
+    pencil_marks: Option<Box<[u16; 81]>>,
+    /// Region of every cell of a 9x9 Jigsaw Sudoku, indexed by row then column, which replaces the groups. `None` for the usual square groups.
+    /// It is boxed for the same reason as the pencil marks.
+    regions: Option<Box<[[u8; 9]; 9]>>,
+    /// Moves made with `set_tracked()`, if any, so that they can be undone. It is boxed for the same reason as the pencil marks.
+    history: Option<Box<History>>,
+    /// Digits used in each row, then each column, then each group (in the order of `units()`), then the main diagonal and the anti-diagonal,
+    /// as bitmasks where bit `v` is set when the digit `v` is present.
+    /// They are kept in sync by `set()` so that `check()` doesn't have to scan the units.
+    masks: Box<[u32]>
+}
+
+impl SudokuGrid {
+    /// Sets the value of the cell in column `x` and row `y` (both from 0 to 8 in a 9x9 grid, starting at the top left corner). 0 empties the cell.
+    pub fn set(&mut self, x:usize, y:usize, value: u8) {
+        self.data[y * self.size + x] = value;
+        self.update_masks(x, y)
+    }
+
+    /// Sets the value of the cell like `set()`, but records the move so that it can be taken back with `undo()`.
+    /// A new move clears the moves that were undone, so they can't be redone anymore. Setting a cell to the value it already holds isn't recorded.
+    pub fn set_tracked(&mut self, x: usize, y: usize, value: u8) {
+        let old = self.get(x, y);
+        if old == value {
+            return;
+        }
+
+        let history = self.history.get_or_insert_with(Box::default);
+        history.moves.push((x, y, old, value));
+        history.undone.clear();
+        self.set(x, y, value)
+    }
+
+    /// Takes back the last move made with `set_tracked()`, restoring the previous value of its cell.
+    /// Returns false if there is no move to undo.
+    pub fn undo(&mut self) -> bool {
+        let Some((x, y, old, new)) = self.history.as_mut().and_then(|history| history.moves.pop()) else {
+            return false;
+        };
+
+        self.set(x, y, old);
+        self.history.get_or_insert_with(Box::default).undone.push((x, y, old, new));
+        true
+    }
+
+    /// Makes again the last move taken back with `undo()`.
+    /// Returns false if there is no move to redo.
+    pub fn redo(&mut self) -> bool {
+        let Some((x, y, old, new)) = self.history.as_mut().and_then(|history| history.undone.pop()) else {
+            return false;
+        };
+
+        self.set(x, y, new);
+        self.history.get_or_insert_with(Box::default).moves.push((x, y, old, new));
+        true
+    }
+
+    /// Returns the length of a side of the grid, which is also the amount of digits (9 for a classic grid).
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the length of a side of a group (3 for a classic grid).
+    pub fn box_size(&self) -> usize {
+        self.box_size
+    }
+
+    /// Returns the rules the grid follows.
+    pub fn kind(&self) -> GridKind {
+        self.kind
+    }
+
+    /// Changes the rules the grid follows. `check()`, and therefore the solver, pick up the new rules right away.
+    pub fn set_kind(&mut self, kind: GridKind) {
+        self.kind = kind
+    }
+
+    /// Returns the region of every cell, indexed by row then column, if the grid is a Jigsaw Sudoku.
+    pub fn regions(&self) -> Option<&[[u8; 9]; 9]> {
+        self.regions.as_deref()
+    }
+
+    /// Makes the grid a Jigsaw Sudoku whose groups are the specified regions (numbered from 0 to 8, indexed by row then column), or a regular grid again with `None`.
+    /// `check()`, and therefore the solver, pick up the regions right away.
+    /// Returns an error if the grid isn't 9x9 or if a region doesn't hold exactly 9 cells.
+    pub fn set_regions(&mut self, regions: Option<[[u8; 9]; 9]>) -> Result<(), String> {
+        if let Some(regions) = &regions {
+            if self.size != 9 {
+                return Err(String::from("only 9x9 grids can have regions."))
+            }
+            if let Some(region) = (0..9).find(|&region| regions.iter().flatten().filter(|&&r| r == region).count() != 9) {
+                return Err(format!("region {} doesn't hold 9 cells.", region + 1))
+            }
+        }
+
+        self.regions = regions.map(Box::new);
+        // The masks of the groups follow the regions
+        for i in 0..self.data.len() {
+            self.update_masks(i % self.size, i / self.size)
+        }
+        Ok(())
+    }
+
+    /// Returns the index of the group holding the specified cell: its region in a Jigsaw Sudoku, or its square group numbered left to right, then top to bottom.
+    pub fn region(&self, x: usize, y: usize) -> usize {
+        match &self.regions {
+            Some(regions) => regions[y][x] as usize,
+            None => y / self.box_size * self.box_size + x / self.box_size
+        }
+    }
+
+    /// Returns the coordinates of the cells of the specified group (see `region()`), row by row.
+    pub fn region_cells(&self, region: usize) -> Vec<(usize, usize)> {
+        let b = self.box_size;
+        match &self.regions {
+            Some(regions) => (0..81).map(|i| (i % 9, i / 9)).filter(|&(x, y)| regions[y][x] as usize == region).collect(),
+            None => (0..self.size).map(|i| (region % b * b + i % b, region / b * b + i / b)).collect()
+        }
+    }
+
+    /// Returns the coordinates of the cells of every unit of the grid, like `units_of_size()`, with the regions of a Jigsaw Sudoku as its groups.
+    pub fn all_units(&self) -> Vec<Vec<(usize, usize)>> {
+        match self.regions {
+            Some(_) => {
+                let mut units = units_of_size(self.size);
+                units.truncate(2 * self.size);
+                units.extend((0..self.size).map(|region| self.region_cells(region)));
+                units
+            },
+            None => units_of_size(self.size)
+        }
+    }
+
+    /// Returns the cages of the grid, empty unless it is a Killer Sudoku.
+    pub fn cages(&self) -> &[Cage] {
+        &self.cages
+    }
+
+    /// Makes the grid a Killer Sudoku with the specified cages (or a regular grid again with no cage). `check()`, and therefore the solver, pick up the cages right away.
+    pub fn set_cages(&mut self, cages: Vec<Cage>) {
+        self.cages = cages.into_boxed_slice()
+    }
+
+    /// Returns the values of the main diagonal (from the top left corner) or of the anti-diagonal (from the top right corner), from top to bottom.
+    pub fn diagonal(&self, anti: bool) -> Vec<u8> {
+        (0..self.size).map(|y| self.get(if anti { self.size - 1 - y } else { y }, y)).collect()
+    }
+
+    /// Returns the cells a chess knight's move away from the specified cell, skipping the moves that would leave the grid.
+    pub fn knight_neighbors(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        KNIGHT_MOVES.iter()
+            .filter_map(|&(dx, dy)| Some((x.checked_add_signed(dx)?, y.checked_add_signed(dy)?)))
+            .filter(|&(x, y)| x < self.size && y < self.size)
+            .collect()
+    }
+
+    /// Recomputes the bitmasks of the row, column and group of the specified cell.
+    /// The units are rescanned rather than patched, so that the masks stay right even when a digit is duplicated.
+    fn update_masks(&mut self, x: usize, y: usize) {
+        let size = self.size;
+        let mask = |cells: &mut dyn Iterator<Item = (usize, usize)>| cells.fold(0u32, |mask, (x, y)| match self.get(x, y) {
+            0 => mask,
+            value => mask | 1 << value
+        });
+
+        let region = self.region(x, y);
+        let row_mask = mask(&mut (0..size).map(|x| (x, y)));
+        let column_mask = mask(&mut (0..size).map(|y| (x, y)));
+        let group_mask = match self.regions {
+            Some(_) => mask(&mut self.region_cells(region).into_iter()),
+            // Square groups are walked without collecting their cells, since this runs on every change of the grid
+            None => mask(&mut (0..size).map(|i| (region % self.box_size * self.box_size + i % self.box_size, region / self.box_size * self.box_size + i / self.box_size)))
+        };
+        // The diagonals are tracked whatever the kind of the grid, so that the kind can change at any time
+        let diagonal_mask = (x == y).then(|| mask(&mut (0..size).map(|i| (i, i))));
+        let anti_diagonal_mask = (x + y == size - 1).then(|| mask(&mut (0..size).map(|i| (size - 1 - i, i))));
+
+        self.masks[y] = row_mask;
+        self.masks[size + x] = column_mask;
+        self.masks[2 * size + region] = group_mask;
+        if let Some(diagonal_mask) = diagonal_mask {
+            self.masks[3 * size] = diagonal_mask
+        }
+        if let Some(anti_diagonal_mask) = anti_diagonal_mask {
+            self.masks[3 * size + 1] = anti_diagonal_mask
+        }
+    }
+
+    /// Returns the value of the cell in column `x` and row `y`, or 0 if the cell is empty or out of the grid.
+    pub fn get(&self, x: usize, y: usize) -> u8 {
+        match self.data.get(y * self.size + x) {
+            Some(&num) => num,
+            None => 0
+        }
+    }
+
+    /// Returns a vec of all the values in the specified row of the grid, from left to right. Rows are numbered from 0 at the top.
+    pub fn row(&self, y: usize) -> Vec<u8> {
+        let mut row_contents = Vec::with_capacity(self.size);
+
+        for x in 0..self.size {
+            let value = self.get(x, y);
+            row_contents.push(value);
+        }
+
+        row_contents
+    }
+
+    /// Returns a vec of all the values in the specified column of the grid, from top to bottom. Columns are numbered from 0 on the left.
+    pub fn column(&self, x: usize) -> Vec<u8> {
+        let mut column_contents = Vec::with_capacity(self.size);
+
+        for y in 0..self.size {
+            let value = self.get(x, y);
+            column_contents.push(value)
+        }
+
+        column_contents
+    }
+
+    /// Returns a vec of all the values in the specified group (3*3 cell in a 9x9 grid, or region of a Jigsaw Sudoku) of the grid, row by row.
+    /// The group is the one holding the cell in column `x` and row `y`.
+    pub fn group(&self, x: usize, y:usize) -> Vec<u8> {
+        self.region_cells(self.region(x, y)).into_iter().map(|(x, y)| self.get(x, y)).collect()
+    }
+
+    /// Checks whether two distinct cells of the grid, given as (x, y), see each other, i.e. share a row, a column or a group (see `region()`).
+    /// A cell doesn't see itself.
+    pub fn sees(&self, a: (usize, usize), b: (usize, usize)) -> bool {
+        a != b && (a.0 == b.0 || a.1 == b.1 || self.region(a.0, a.1) == self.region(b.0, b.1))
+    }
+
+    /// Checks whether the given value can be inserted in the given location (assuming there is no value already).
+    /// This check is done according to the sudoku rules:
+    /// - All digits on the row must be unique
+    /// - All digits on the column must be unique
+    /// - All digits in the 3x3 group must be unique
+    /// - All digits on each main diagonal must be unique, in an X-Sudoku (`GridKind::Diagonal`)
+    /// - The digit can't be a knight's move away from the same digit, in an Anti-Knight Sudoku (`GridKind::AntiKnight`)
+    /// - All digits of a cage must be unique, and must still be able to add up to its sum, in a Killer Sudoku
+    /// - The digit must be one of the pencil marks of the cell, in a grid created with `from_candidates()`
+    pub fn check(&self, x: usize, y: usize, value: u8) -> bool {
+        let size = self.size;
+        let mut used = self.masks[y] | self.masks[size + x] | self.masks[2 * size + self.region(x, y)];
+        if self.kind == GridKind::Diagonal {
+            if x == y {
+                used |= self.masks[3 * size]
+            }
+            if x + y == size - 1 {
+                used |= self.masks[3 * size + 1]
+            }
+        } else if self.kind == GridKind::AntiKnight && self.knight_neighbors(x, y).iter().any(|&(x, y)| self.get(x, y) == value) {
+            return false
+        }
+        // Pencil marks use bit `v - 1` for the digit `v`
+        let marked = self.pencil_marks.as_ref().is_none_or(|marks| u32::from(marks[y * size + x]) << 1 & 1 << value != 0);
+        used & 1 << value == 0 && marked && self.cages.iter().all(|cage| !cage.cells.contains(&(x, y)) || cage.fits(self, Some((x, y, value))))
+    }
+
+    /// Checks if the grid can be solved or not: the grid must hold at least one value and follow the rules (see `validate()`).
+    /// A Killer Sudoku may have no value, since its cages hold the clues, but the sum of each cage must still be reachable.
+    pub fn check_grid(&self) -> bool {
+        (!self.is_empty() || !self.cages.is_empty()) && self.validate().is_ok() && self.cages.iter().all(|cage| cage.fits(self, None))
+    }
+
+    /// Checks that no digit is repeated in a row, column or group (or diagonal in an X-Sudoku, or cage in a Killer Sudoku) of the grid,
+    /// and that no digit is a knight's move away from itself in an Anti-Knight Sudoku.
+    /// Returns the first cell, in row-major order, whose digit is repeated along with the unit the digit is repeated in.
+    pub fn validate(&self) -> Result<(), Conflict> {
+        let repeated = |values: Vec<u8>, value: u8| values.iter().filter(|&&v| v == value).count() > 1;
+
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let value = self.get(x, y);
+                if value == 0 {
+                    continue
+                }
+
+                // We filter and count occurrences because in opposition to `check()` the value we check for is already present.
+                let kind = if repeated(self.row(y), value) {
+                    UnitKind::Row
+                } else if repeated(self.column(x), value) {
+                    UnitKind::Column
+                } else if repeated(self.group(x, y), value) {
+                    UnitKind::Group
+                } else if self.kind == GridKind::Diagonal && ((x == y && repeated(self.diagonal(false), value)) || (x + y == self.size - 1 && repeated(self.diagonal(true), value))) {
+                    UnitKind::Diagonal
+                } else if self.kind == GridKind::AntiKnight && self.knight_neighbors(x, y).iter().any(|&(x, y)| self.get(x, y) == value) {
+                    UnitKind::KnightMove
+                } else if self.cages.iter().any(|cage| cage.cells.contains(&(x, y)) && cage.cells.iter().filter(|&&(x, y)| self.get(x, y) == value).count() > 1) {
+                    UnitKind::Cage
+                } else {
+                    continue
+                };
+                return Err(Conflict { kind, x, y, value })
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the grid follows the classic rules as well as the additional rules of a variant.
+    /// Unlike `check_grid()`, an empty grid is accepted when there are additional rules, since they hold the clues of such puzzles.
+    pub fn check_grid_with(&self, constraints: &[Box<dyn Constraint>]) -> bool {
+        let classic = if constraints.is_empty() || !self.is_empty() { self.check_grid() } else { true };
+        classic && constraints.iter().all(|constraint| constraint.check_grid(self))
+    }
+
+    /// Returns the digits that can legally be placed in the specified cell, or an empty vec if the cell is already filled.
+    pub fn candidates(&self, x: usize, y: usize) -> Vec<u8> {
+        if self.get(x, y) != 0 {
+            return Vec::new()
+        }
+
+        (1..=self.size as u8).filter(|&value| self.check(x, y, value)).collect()
+    }
+
+    /// Returns the candidates of every cell of a 9x9 grid as bitmasks, indexed by row then column, where bit `i` is set when the digit `i + 1` is a candidate.
+    /// Filled cells have no candidate. Returns `None` if the grid isn't 9x9.
+    pub fn candidates_mask(&self) -> Option<[[u16; 9]; 9]> {
+        (self.size == 9).then(|| self.candidate_bitmasks())
+    }
+
+    /// Bitmasks of `candidates_mask()`, for callers that already checked that the grid is 9x9.
+    fn candidate_bitmasks(&self) -> [[u16; 9]; 9] {
+        std::array::from_fn(|y| std::array::from_fn(|x| self.candidates(x, y).iter().fold(0, |mask, &value| mask | 1 << (value - 1))))
+    }
+
+    /// Returns every empty cell that has a single candidate left (naked single) along with that candidate, as (x, y, value) in row-major order.
+    /// The cells are found at once on the current grid: placing one of the digits may turn other cells into naked singles.
+    pub fn naked_singles(&self) -> Vec<(usize, usize, u8)> {
+        self.empty_cells().into_iter().filter_map(|(x, y)| match self.candidates(x, y).as_slice() {
+            &[value] => Some((x, y, value)),
+            _ => None
+        }).collect()
+    }
+
+    /// Returns the coordinates of every empty cell, in row-major order.
+    pub fn empty_cells(&self) -> Vec<(usize, usize)> {
+        (0..self.data.len()).filter(|&i| self.data[i] == 0).map(|i| (i % self.size, i / self.size)).collect()
+    }
+
+    /// Returns the amount of filled cells, which are the clues of a puzzle.
+    pub fn filled_count(&self) -> usize {
+        self.data.iter().filter(|&&v| v != 0).count()
+    }
+
+    /// Returns the amount of empty cells, the cells `empty_cells()` lists.
+    pub fn empty_count(&self) -> usize {
+        self.data.len() - self.filled_count()
+    }
+
+    /// Returns the coordinates of every cell whose digit is repeated in one of its units (the diagonals of an X-Sudoku included),
+    /// or a knight's move away in an Anti-Knight Sudoku, in row-major order.
+    /// Unlike `validate()`, which stops at the first conflict, all of them are found, so that they can be highlighted.
+    pub fn conflicts(&self) -> Vec<(usize, usize)> {
+        let mut units = self.all_units();
+        if self.kind == GridKind::Diagonal {
+            units.push((0..self.size).map(|y| (y, y)).collect());
+            units.push((0..self.size).map(|y| (self.size - 1 - y, y)).collect());
+        }
+
+        let mut conflicting = vec![false; self.data.len()];
+        for unit in units {
+            for (i, &(x, y)) in unit.iter().enumerate() {
+                let value = self.get(x, y);
+                if value == 0 {
+                    continue
+                }
+                for &(other_x, other_y) in &unit[i + 1..] {
+                    if self.get(other_x, other_y) == value {
+                        conflicting[y * self.size + x] = true;
+                        conflicting[other_y * self.size + other_x] = true;
+                    }
+                }
+            }
+        }
+        if self.kind == GridKind::AntiKnight {
+            for i in (0..self.data.len()).filter(|&i| self.data[i] != 0) {
+                if self.knight_neighbors(i % self.size, i / self.size).iter().any(|&(x, y)| self.get(x, y) == self.data[i]) {
+                    conflicting[i] = true
+                }
+            }
+        }
+
+        (0..self.data.len()).filter(|&i| conflicting[i]).map(|i| (i % self.size, i / self.size)).collect()
+    }
+
+    /// Returns the sum of the candidate counts of every empty cell, a cheap measure of how constrained the grid is.
+    pub fn total_candidates(&self) -> usize {
+        (0..self.data.len()).map(|i| self.candidates(i % self.size, i / self.size).len()).sum()
+    }
+
+    /// Returns the coordinates of the empty cells that have exactly `k` candidates, in row-major order.
+    pub fn cells_with_candidate_count(&self, k: usize) -> Vec<(usize, usize)> {
+        self.empty_cells()
+            .into_iter()
+            .filter(|&(x, y)| self.candidates(x, y).len() == k)
+            .collect()
+    }
+
+    /// Returns true if the grid has reached a dead end: an empty cell has no candidate left, or a digit missing from a unit can't go in any of its cells.
+    /// This is the standard test used to prune a branch of a search.
+    pub fn is_contradictory(&self) -> bool {
+        if self.empty_cells().iter().any(|&(x, y)| self.candidates(x, y).is_empty()) {
+            return true
+        }
+
+        self.all_units().iter().any(|unit| {
+            (1..=self.size as u8).any(|value| {
+                unit.iter().all(|&(x, y)| self.get(x, y) != value)
+                    && unit.iter().all(|&(x, y)| self.get(x, y) != 0 || !self.check(x, y, value))
+            })
+        })
+    }
+
+    /// Returns the average number of candidates of the cells left empty once singles are propagated, i.e. the cells where a solver would have to branch.
+    /// A higher branching factor usually means a harder search. Returns 0.0 if propagation fills the whole grid or runs into a contradiction.
+    pub fn average_branching(&self) -> f32 {
+        let mut propagated = self.clone();
+        if propagate_singles(&mut propagated).is_err() {
+            return 0.0
+        }
+
+        let counts: Vec<usize> = propagated.empty_cells()
+            .iter()
+            .map(|&(x, y)| propagated.candidates(x, y).len())
+            .collect();
+        if counts.is_empty() {
+            return 0.0
+        }
+
+        counts.iter().sum::<usize>() as f32 / counts.len() as f32
+    }
+
+    /// Returns the empty cell with the fewest candidates along with its candidates, or `None` if the grid is full.
+    pub fn most_constrained_cell(&self) -> Option<(usize, usize, Vec<u8>)> {
+        let mut best: Option<(usize, usize, Vec<u8>)> = None;
+
+        for y in 0..self.size {
+            for x in 0..self.size {
+                if self.get(x, y) == 0 {
+                    let candidates = self.candidates(x, y);
+                    if best.as_ref().is_none_or(|(_, _, best_candidates)| candidates.len() < best_candidates.len()) {
+                        // A cell without candidates can't get any more constrained
+                        let dead_end = candidates.is_empty();
+                        best = Some((x, y, candidates));
+                        if dead_end {
+                            return best
+                        }
+                    }
+                }
+            }
+        }
+
+        best
+    }
+
+    /// Returns the next logical move along with the technique that justifies it, or `None` if no single-step deduction is available and the solver would have to guess.
+    /// Naked singles are looked for first, then hidden singles in rows, columns and groups.
+    pub fn hint(&self) -> Option<Hint> {
+        self.hint_with(|x, y, value| self.check(x, y, value))
+    }
+
+    /// Looks for a single like `hint()`, where `is_candidate(x, y, value)` tells whether a digit is still a candidate of an empty cell.
+    /// This lets the candidates narrowed by eliminations be taken into account.
+    fn hint_with(&self, is_candidate: impl Fn(usize, usize, u8) -> bool) -> Option<Hint> {
+        let naked_single = self.empty_cells().into_iter().find_map(|(x, y)| match (1..=self.size as u8).filter(|&value| is_candidate(x, y, value)).collect::<Vec<_>>().as_slice() {
+            &[value] => Some(Hint { x, y, value, technique: Technique::NakedSingle }),
+            _ => None
+        });
+        if naked_single.is_some() {
+            return naked_single
+        }
+
+        // Units come as the rows, then the columns, then the groups
+        self.all_units().iter().enumerate().find_map(|(index, unit)| {
+            let kind = [UnitKind::Row, UnitKind::Column, UnitKind::Group][index / self.size];
+            (1..=self.size as u8)
+                .filter(|&value| unit.iter().all(|&(x, y)| self.get(x, y) != value))
+                .find_map(|value| match unit.iter().filter(|&&(x, y)| self.get(x, y) == 0 && is_candidate(x, y, value)).collect::<Vec<_>>().as_slice() {
+                    &[&(x, y)] => Some(Hint { x, y, value, technique: Technique::HiddenSingle(kind) }),
+                    _ => None
+                })
+        })
+    }
+
+    /// Returns an iterator over every solution of the grid, found one at a time by backtracking: the search is suspended between two solutions.
+    /// There is no iteration cap, and an under-constrained grid has an astronomical amount of solutions (an empty grid has about 6.67e21 of them),
+    /// so callers should bound the iteration with `take()`. An invalid grid has no solution.
+    pub fn solutions(self) -> impl Iterator<Item = SudokuGrid> {
+        let valid = self.is_empty() || self.check_grid();
+        Solutions { grid: self, decisions: Vec::new(), started: !valid }
+    }
+
+    /// Returns the indices of the rows, columns and groups that are completely and correctly filled (each digit present exactly once).
+    /// Groups are numbered from 0 (0 to 8 in a 9x9 grid), left to right then top to bottom.
+    pub fn completed_units(&self) -> (Vec<usize>, Vec<usize>, Vec<usize>) {
+        let rows = (0..self.size).filter(|&y| self.is_row_complete(y)).collect();
+        let columns = (0..self.size).filter(|&x| self.is_column_complete(x)).collect();
+        let groups = (0..self.size).filter(|&i| is_complete_unit(&self.region_cells(i).iter().map(|&(x, y)| self.get(x, y)).collect::<Vec<u8>>())).collect();
+
+        (rows, columns, groups)
+    }
+
+    /// Checks whether the row `y` holds each digit exactly once.
+    pub fn is_row_complete(&self, y: usize) -> bool {
+        is_complete_unit(&self.row(y))
+    }
+
+    /// Checks whether the column `x` holds each digit exactly once.
+    pub fn is_column_complete(&self, x: usize) -> bool {
+        is_complete_unit(&self.column(x))
+    }
+
+    /// Checks whether the group holding the cell in column `x` and row `y` holds each digit exactly once.
+    pub fn is_box_complete(&self, x: usize, y: usize) -> bool {
+        is_complete_unit(&self.group(x, y))
+    }
+
+    /// Returns the fraction (from 0.0 to 1.0) of the cells left empty by `puzzle` that this grid fills with the same digit as `solution`,
+    /// or `None` if the three grids don't have the same size. A puzzle without empty cells counts as fully done.
+    /// A grid doesn't remember which of its digits were given, so `puzzle` is what tells the clues, which don't count as progress, apart from the digits filled afterwards.
+    pub fn progress_against(&self, puzzle: &SudokuGrid, solution: &SudokuGrid) -> Option<f32> {
+        if puzzle.size != self.size || solution.size != self.size {
+            return None
+        }
+        let to_fill: Vec<usize> = (0..self.data.len()).filter(|&i| puzzle.data[i] == 0).collect();
+        if to_fill.is_empty() {
+            return Some(1.0)
+        }
+
+        let correct = to_fill.iter().filter(|&&i| self.data[i] != 0 && self.data[i] == solution.data[i]).count();
+        Some(correct as f32 / to_fill.len() as f32)
+    }
+
+    /// Returns true if every cell of the grid holds a value.
+    pub fn is_complete(&self) -> bool {
+        !self.data.contains(&0)
+    }
+
+    /// Returns true if the grid is completely and correctly filled: every row, column and group (and diagonal in an X-Sudoku) holds each digit exactly once,
+    /// no digit is a knight's move away from itself in an Anti-Knight Sudoku, and the digits of every cage of a Killer Sudoku add up to its sum.
+    pub fn is_solved(&self) -> bool {
+        let group = |i: usize| self.region_cells(i).iter().map(|&(x, y)| self.get(x, y)).collect::<Vec<u8>>();
+        let diagonals = self.kind != GridKind::Diagonal || (is_complete_unit(&self.diagonal(false)) && is_complete_unit(&self.diagonal(true)));
+        let knights = self.kind != GridKind::AntiKnight || (0..self.data.len()).all(|i| self.knight_neighbors(i % self.size, i / self.size).iter().all(|&(x, y)| self.get(x, y) != self.data[i]));
+        let cages = self.cages.iter().all(|cage| cage.fits(self, None));
+        diagonals && knights && cages && (0..self.size).all(|i| is_complete_unit(&self.row(i)) && is_complete_unit(&self.column(i)) && is_complete_unit(&group(i)))
+    }
+
+    /// Returns true if the grid is a solution of the specified puzzle: it is solved (see `is_solved()`) and holds every clue of the puzzle.
+    /// Unlike solving the puzzle and comparing the grids, this accepts any of the solutions of a puzzle that has several.
+    pub fn is_solution_of(&self, puzzle: &SudokuGrid) -> bool {
+        self.is_solved() && self.data.len() == puzzle.data.len() && puzzle.data.iter().zip(&self.data).all(|(&clue, &value)| clue == 0 || clue == value)
+    }
+
+    /// Returns every cell where this grid and `other` hold different values, as (x, y, value in this grid, value in `other`) in row-major order, with 0 for an empty cell.
+    /// Cells are compared by their coordinates, so both grids should have the same size.
+    pub fn diff(&self, other: &SudokuGrid) -> Vec<(usize, usize, u8, u8)> {
+        (0..self.data.len())
+            .map(|i| (i % self.size, i / self.size))
+            .filter_map(|(x, y)| {
+                let (value, other_value) = (self.get(x, y), other.get(x, y));
+                (value != other_value).then_some((x, y, value, other_value))
+            })
+            .collect()
+    }
+
+    /// Returns the grid turned a quarter turn clockwise: the top row becomes the right column.
+    /// Like the other geometric transforms, it keeps a valid grid valid, and keeps the kind of the grid, but not its cages, pencil marks, regions or move history.
+    pub fn rotate90(&self) -> SudokuGrid {
+        let n = self.size - 1;
+        self.transformed(|x, y| (y, n - x))
+    }
+
+    /// Returns the grid turned a half turn.
+    pub fn rotate180(&self) -> SudokuGrid {
+        let n = self.size - 1;
+        self.transformed(|x, y| (n - x, n - y))
+    }
+
+    /// Returns the grid turned a quarter turn counterclockwise: the top row becomes the left column.
+    pub fn rotate270(&self) -> SudokuGrid {
+        let n = self.size - 1;
+        self.transformed(|x, y| (n - y, x))
+    }
+
+    /// Returns the grid mirrored across its middle row, swapping its top and bottom rows (like `Symmetry::Horizontal`).
+    pub fn mirror_horizontal(&self) -> SudokuGrid {
+        let n = self.size - 1;
+        self.transformed(|x, y| (x, n - y))
+    }
+
+    /// Returns the grid mirrored across its middle column, swapping its left and right columns (like `Symmetry::Vertical`).
+    pub fn mirror_vertical(&self) -> SudokuGrid {
+        let n = self.size - 1;
+        self.transformed(|x, y| (n - x, y))
+    }
+
+    /// Returns the grid mirrored across its main diagonal: the rows become the columns.
+    pub fn transpose(&self) -> SudokuGrid {
+        self.transformed(|x, y| (y, x))
+    }
+
+    /// Returns the grid with its digits relabeled: the digit `d` becomes `mapping[d - 1]`, and empty cells stay empty (the convention of `symmetry_relabeling()`).
+    /// Relabeling keeps a valid grid valid and a unique puzzle unique. Like the geometric transforms, the kind of the grid is kept, but not its cages, pencil marks, regions or move history.
+    /// Returns an error if the grid isn't 9x9 or if the mapping isn't a permutation of the digits 1 to 9.
+    pub fn relabel(&self, mapping: [u8; 9]) -> Result<SudokuGrid, String> {
+        if self.size != 9 {
+            return Err(String::from("only 9x9 grids can be relabeled."))
+        }
+        if let Some(digit) = (1..=9).find(|digit| !mapping.contains(digit)) {
+            return Err(format!("the mapping isn't a permutation of the digits, {} is missing.", digit))
+        }
+
+        let data: Vec<u8> = self.data.iter().map(|&v| if v == 0 { 0 } else { mapping[v as usize - 1] }).collect();
+        let mut grid = SudokuGrid::from_cells(&data);
+        grid.kind = self.kind;
+        Ok(grid)
+    }
+
+    /// Returns a random permutation of the digits 1 to 9 for `relabel()`, drawn from the specified RNG.
+    pub fn random_relabeling(rng: &mut impl Rng) -> [u8; 9] {
+        let mut mapping = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+        mapping.shuffle(rng);
+        mapping
+    }
+
+    /// Builds a grid of the same size and kind where the cell in column `x` and row `y` takes the value of the cell at `source(x, y)` in this grid.
+    fn transformed(&self, source: impl Fn(usize, usize) -> (usize, usize)) -> SudokuGrid {
+        let data: Vec<u8> = (0..self.data.len()).map(|i| {
+            let (x, y) = source(i % self.size, i / self.size);
+            self.get(x, y)
+        }).collect();
+
+        let mut grid = SudokuGrid::from_sized_cells(self.size, &data);
+        grid.kind = self.kind;
+        grid
+    }
+
+    /// Returns the sums of the values of each row, column and group (groups numbered left to right, then top to bottom).
+    /// Every sum of a solved grid is 45, which makes this a cheap sanity check. Returns `None` if the grid isn't 9x9.
+    pub fn unit_sums(&self) -> Option<([u16; 9], [u16; 9], [u16; 9])> {
+        if self.size != 9 {
+            return None
+        }
+        let sum = |values: Vec<u8>| values.iter().map(|&v| v as u16).sum::<u16>();
+
+        Some((
+            std::array::from_fn(|y| sum(self.row(y))),
+            std::array::from_fn(|x| sum(self.column(x))),
+            std::array::from_fn(|i| sum(self.region_cells(i).iter().map(|&(x, y)| self.get(x, y)).collect()))
+        ))
+    }
+
+    /// Returns the contents of the grid as a vec of rows, which is how grids are represented in JSON.
+    pub fn rows(&self) -> Vec<Vec<u8>> {
+        (0..self.size).map(|y| self.row(y)).collect()
+    }
+
+    /// Creates a grid from its rows, the representation of grids in JSON.
+    /// Returns an error if there aren't 9 rows of 9 values between 0 and 9.
+    pub fn from_rows(rows: &[Vec<u8>]) -> Result<SudokuGrid, String> {
+        if rows.len() != 9 {
+            return Err(format!("a grid holds 9 rows, got {}.", rows.len()))
+        }
+        if let Some(y) = rows.iter().position(|row| row.len() != 9) {
+            return Err(format!("a row holds 9 values, got {} in row {}.", rows[y].len(), y + 1))
+        }
+
+        SudokuGrid::from_data(&rows.concat())
+    }
+
+    /// Returns the grid as a JSON array of rows.
+    pub fn to_json(&self) -> String {
+        json!(self.rows()).to_string()
+    }
+
+    /// Returns the grid as CSV: a line per row, with the values separated by commas and empty cells left blank, as spreadsheets expect.
+    pub fn to_csv(&self) -> String {
+        self.rows().iter().map(|row| row.iter().map(|&v| if v == 0 { String::new() } else { v.to_string() }).collect::<Vec<String>>().join(",") + "\n").collect()
+    }
+
+    /// Parses a grid from CSV: 9 lines of 9 comma-separated cells, where a blank cell, `0` or `.` is empty. Fields may be quoted, and blank lines are skipped.
+    /// Returns an error if a cell isn't a digit or if the data isn't 9 rows of 9 cells.
+    pub fn from_csv(csv: &str) -> Result<SudokuGrid, String> {
+        let rows = csv
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .enumerate()
+            .map(|(y, line)| split_csv_line(line).iter().enumerate().map(|(x, field)| match field.trim() {
+                "" | "0" | "." => Ok(0),
+                field => field.parse::<u8>().ok().filter(|v| (1..=9).contains(v)).ok_or(format!("the cell '{}' at R{}C{} isn't a digit.", field, y + 1, x + 1))
+            }).collect::<Result<Vec<u8>, String>>())
+            .collect::<Result<Vec<Vec<u8>>, String>>()?;
+
+        SudokuGrid::from_rows(&rows)
+    }
+
+    /// Parses a grid from a JSON array of 9 rows, each holding 9 values between 0 and 9.
+    pub fn from_json(json: &str) -> Result<SudokuGrid, String> {
+        let rows = serde_json::from_str::<Vec<Vec<u8>>>(json).map_err(|err| format!("invalid grid JSON: {}", err))?;
+        SudokuGrid::from_rows(&rows)
+    }
+
+    /// Returns the 81 values of the grid on a single line, separated by the specified delimiter.
+    pub fn to_flat_string(&self, delimiter: char) -> String {
+        self.data.iter().map(|v| v.to_string()).collect::<Vec<String>>().join(&delimiter.to_string())
+    }
+
+    /// Returns the grid in the standard 81-character line format used by most Sudoku tools, where `.` marks an empty cell.
+    /// Larger grids use the hexadecimal digits of `digit_symbol()`.
+    pub fn to_line_string(&self) -> String {
+        self.data.iter().map(|&v| if v == 0 { '.' } else { digit_symbol(v, self.size) }).collect()
+    }
+
+    /// Returns the grid in the SDK format: 9 lines of 9 characters, where `.` marks an empty cell.
+    pub fn to_sdk_string(&self) -> String {
+        let line = self.to_line_string();
+        line.as_bytes().chunks(self.size).map(|row| format!("{}\n", String::from_utf8_lossy(row))).collect()
+    }
+
+    /// Returns the grid as a Rust `vec!` literal laid out like `example_grid()`: a line per row with wider spacing between groups, and a blank line between bands of groups.
+    pub fn to_rust_literal(&self) -> String {
+        let mut s = String::from("vec![\n");
+
+        let (size, box_size) = (self.size, self.box_size);
+
+        for y in 0..size {
+            if y != 0 && y % box_size == 0 {
+                s.push('\n')
+            }
+
+            let row = (0..box_size)
+                .map(|group| (0..box_size).map(|i| self.get(group * box_size + i, y).to_string()).collect::<Vec<String>>().join(", "))
+                .collect::<Vec<String>>()
+                .join(",   ");
+            // The last value has no trailing comma
+            s.push_str(&format!("    {}{}\n", row, if y == size - 1 { "" } else { "," }));
+        }
+
+        s.push(']');
+        s
+    }
+
+    /// Returns an SVG image of the grid: thin lines between cells, thick lines on the group boundaries, and the digits centered in their cells.
+    /// Empty cells are left blank.
+    pub fn to_svg(&self) -> String {
+        const CELL: usize = 50;
+        const MARGIN: usize = 5;
+        let side = self.size * CELL + 2 * MARGIN;
+
+        let mut s = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" viewBox=\"0 0 {0} {0}\">\n", side);
+        s.push_str(&format!("  <rect x=\"0\" y=\"0\" width=\"{0}\" height=\"{0}\" fill=\"white\"/>\n", side));
+
+        // Thick lines are drawn last so that thin lines don't cover them
+        for thick in [false, true] {
+            for i in (0..=self.size).filter(|i| (i % self.box_size == 0) == thick) {
+                let position = MARGIN + i * CELL;
+                let (start, end, width) = (MARGIN, side - MARGIN, if thick { 3 } else { 1 });
+                s.push_str(&format!("  <line x1=\"{0}\" y1=\"{1}\" x2=\"{0}\" y2=\"{2}\" stroke=\"black\" stroke-width=\"{3}\"/>\n", position, start, end, width));
+                s.push_str(&format!("  <line x1=\"{1}\" y1=\"{0}\" x2=\"{2}\" y2=\"{0}\" stroke=\"black\" stroke-width=\"{3}\"/>\n", position, start, end, width));
+            }
+        }
+
+        for (x, y) in (0..self.data.len()).map(|i| (i % self.size, i / self.size)).filter(|&(x, y)| self.get(x, y) != 0) {
+            s.push_str(&format!("  <text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"32\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>\n",
+                                MARGIN + x * CELL + CELL / 2, MARGIN + y * CELL + CELL / 2, digit_symbol(self.get(x, y), self.size)));
+        }
+
+        s.push_str("</svg>\n");
+        s
+    }
+
+    /// Returns the boxed representation of the `Display` implementation with a header of column numbers and a column of row numbers.
+    /// The numbers start at 1, like in the RXCY notation used to refer to cells. Returns `None` if the grid isn't 9x9, since two-digit numbers wouldn't fit above the columns.
+    pub fn to_labeled_string(&self) -> Option<String> {
+        if self.size != 9 {
+            return None
+        }
+        let mut s = String::from("    123   456   789\n");
+        let mut row_number = 0;
+
+        for line in self.to_string().lines().filter(|line| !line.is_empty()) {
+            if line.starts_with("|-") {
+                s.push_str("  ")
+            } else {
+                row_number += 1;
+                s.push_str(&format!("{} ", row_number))
+            }
+            s.push_str(line);
+            s.push('\n');
+        }
+
+        Some(s)
+    }
+
+    /// Returns a pencil-mark view of a 9x9 grid, where every cell takes 3 lines of 3 characters, or `None` if the grid isn't 9x9.
+    /// Empty cells show their candidates laid out like a keypad (1 at the top left, 9 at the bottom right) and blanks elsewhere, while filled cells show their digit framed in the middle.
+    pub fn to_pencilmark_string(&self) -> Option<String> {
+        if self.size != 9 {
+            return None
+        }
+        let border = format!("|{}|\n", "-".repeat(41));
+        let mut s = border.clone();
+
+        for y in 0..9 {
+            let cells = (0..9).map(|x| self.pencilmark_cell(x, y)).collect::<Vec<[String; 3]>>();
+
+            for line in 0..3 {
+                s.push_str("| ");
+                for (x, cell) in cells.iter().enumerate() {
+                    if x != 0 {
+                        s.push_str(if x % 3 == 0 { " | " } else { " " })
+                    }
+                    s.push_str(&cell[line]);
+                }
+                s.push_str(" |\n");
+            }
+
+            if (y + 1) % 3 == 0 {
+                s.push_str(&border)
+            } else {
+                // Blank line keeping the group separators, so that cells don't run into each other
+                s.push_str(&format!("|{0}|{0}|{0}|\n", " ".repeat(13)))
+            }
+        }
+
+        Some(s)
+    }
+
+    /// Returns the 3 lines of 3 characters representing the specified cell in `to_pencilmark_string()`.
+    fn pencilmark_cell(&self, x: usize, y: usize) -> [String; 3] {
+        match self.get(x, y) {
+            0 => {
+                let candidates = self.candidates(x, y);
+                let mark = |value: u8| if candidates.contains(&value) { char::from(b'0' + value) } else { ' ' };
+                [1, 4, 7].map(|first: u8| (first..first + 3).map(mark).collect())
+            },
+            value => [String::from(".-."), format!("|{}|", value), String::from("'-'")]
+        }
+    }
+
+    /// Returns true if there is no value set in the grid.
+    pub fn is_empty(&self) -> bool {
+        !self.data.iter().any(|&v| v > 0)
+    }
+
+    /// Creates an empty grid
+    pub fn empty() -> SudokuGrid {
+        SudokuGrid::from_cells(&[0; 81])
+    }
+
+    /// Creates a grid with random values.
+    /// The returned grid may not be a valid sudoku grid.
+    pub fn randomly_filled() -> SudokuGrid {
+        SudokuGrid::randomly_filled_with_rng(&mut thread_rng())
+    }
+
+    /// Same as `randomly_filled()`, drawing the random values from the specified RNG so that the grid can be reproduced.
+    pub fn randomly_filled_with_rng(rng: &mut impl Rng) -> SudokuGrid {
+        let mut data: Vec<u8> = vec![0; 81];
+
+        for cell in data.iter_mut() {
+            if rng.gen_range(0..5) == 0 {
+                *cell = rng.gen_range(1..=9)
+            }
+        }
+
+        SudokuGrid::from_cells(&data)
+    }
+
+    /// Creates a complete and valid sudoku grid with random values, see `random_solution()`.
+    pub fn valid_random() -> SudokuGrid {
+        SudokuGrid::random_solution(&mut thread_rng())
+    }
+
+    /// Creates a complete and valid grid with random values.
+    /// The three diagonal groups are independent from each other, so they are filled with shuffled digits first, then the rest is solved with randomized candidate ordering.
+    pub fn random_solution(rng: &mut impl Rng) -> SudokuGrid {
+        let mut grid = SudokuGrid::empty();
+
+        for group in 0..3 {
+            let mut digits: Vec<u8> = (1..=9).collect();
+            digits.shuffle(rng);
+            for (i, &digit) in digits.iter().enumerate() {
+                grid.set(group * 3 + i % 3, group * 3 + i / 3, digit)
+            }
+        }
+
+        fill_randomly(&mut grid, rng);
+        grid
+    }
+
+    /// Creates a puzzle that can be solved by logic alone, without any guessing.
+    /// A random solution is generated from the seed, then clues are removed for as long as `solve_logical_only()` can still complete the puzzle.
+    /// The same seed always produces the same puzzle. At most `max_attempts` solutions are carved (at least one), after which the last puzzle is handed back in `GenError::TargetNotMet`.
+    pub fn generate_no_guess(seed: u64, max_attempts: usize) -> Result<SudokuGrid, GenError> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut best = None;
+
+        for _ in 0..max_attempts.max(1) {
+            let solution = SudokuGrid::random_solution(&mut rng);
+            let puzzle = carve(&solution, &mut rng, Symmetry::None, 0, |puzzle| solve_logical_only(puzzle).is_some());
+            // Removing clues never makes the puzzle unsolvable by logic, but we check the final puzzle anyway and regenerate if needed.
+            if solve_logical_only(&puzzle).as_ref().map(|solved| solved.data == solution.data).unwrap_or(false) {
+                return Ok(puzzle)
+            }
+            best = Some(puzzle);
+        }
+
+        Err(GenError::TargetNotMet { best: best.unwrap_or_else(SudokuGrid::empty) })
+    }
+
+    /// Returns a copy of the grid with `count` of its clues removed at random (or all of them if it holds fewer).
+    /// Removing clues never loses a solution, so a solvable grid (like a full board) stays solvable, but the result is not necessarily uniquely solvable.
+    /// The same seed always removes the same clues.
+    pub fn poke_holes(&self, count: usize, seed: u64) -> SudokuGrid {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut puzzle = self.clone();
+
+        let mut filled: Vec<usize> = (0..self.data.len()).filter(|&i| self.data[i] != 0).collect();
+        filled.shuffle(&mut rng);
+        for i in filled.into_iter().take(count) {
+            puzzle.set(i % self.size, i / self.size, 0)
+        }
+
+        puzzle
+    }
+
+    /// Creates a uniquely solvable puzzle with the specified amount of clues.
+    /// Clues are removed from random solutions while the puzzle stays unique, until a puzzle with exactly `clues` clues is obtained.
+    /// Fails if the clue count can't lead to a sensible puzzle (see `validate_clue_count()`).
+    /// At most `max_attempts` solutions are carved (at least one); if none of them gets down to `clues` clues, the puzzle with the fewest clues is handed back in `GenError::TargetNotMet`.
+    pub fn generate_with_clues(clues: usize, rng: &mut impl Rng, max_attempts: usize) -> Result<SudokuGrid, GenError> {
+        validate_clue_count(clues)?;
+
+        let mut best: Option<(usize, SudokuGrid)> = None;
+        for _ in 0..max_attempts.max(1) {
+            let solution = SudokuGrid::random_solution(rng);
+            let puzzle = carve(&solution, rng, Symmetry::None, clues, |puzzle| count_solutions(puzzle, 2) == 1);
+            let puzzle_clues = puzzle.filled_count();
+            if puzzle_clues == clues {
+                return Ok(puzzle)
+            }
+            if best.as_ref().is_none_or(|(best_clues, _)| puzzle_clues < *best_clues) {
+                best = Some((puzzle_clues, puzzle))
+            }
+        }
+
+        Err(GenError::TargetNotMet { best: best.map(|(_, puzzle)| puzzle).unwrap_or_else(SudokuGrid::empty) })
+    }
+
+    /// Creates a uniquely solvable puzzle of the specified difficulty, as rated by `grade()`.
+    /// A random solution is built, then clues are removed one at a time while the puzzle stays unique and no harder than the target, until the clue count reaches the band of the difficulty.
+    /// Attempts that don't end up with the exact difficulty within the band are thrown away.
+    pub fn generate(difficulty: Difficulty) -> SudokuGrid {
+        SudokuGrid::generate_with_rng(difficulty, &mut thread_rng())
+    }
+
+    /// Same as `generate()`, drawing the random values from the specified RNG so that the puzzle can be reproduced.
+    pub fn generate_with_rng(difficulty: Difficulty, rng: &mut impl Rng) -> SudokuGrid {
+        SudokuGrid::generate_symmetric(difficulty, Symmetry::None, rng)
+    }
+
+    /// Same as `generate_with_rng()`, removing the clues by orbits of the specified symmetry so that the clues of the puzzle follow it.
+    /// Keeps trying until a puzzle is found, see `try_generate_symmetric()` for a bounded generation.
+    pub fn generate_symmetric(difficulty: Difficulty, symmetry: Symmetry, rng: &mut impl Rng) -> SudokuGrid {
+        loop {
+            if let Ok(puzzle) = SudokuGrid::try_generate_symmetric(difficulty, symmetry, rng, 1) {
+                return puzzle
+            }
+        }
+    }
+
+    /// Same as `generate_symmetric()`, but at most `max_attempts` solutions are carved (at least one), after which the last puzzle is handed back in `GenError::TargetNotMet`.
+    pub fn try_generate_symmetric(difficulty: Difficulty, symmetry: Symmetry, rng: &mut impl Rng, max_attempts: usize) -> Result<SudokuGrid, GenError> {
+        let band = difficulty.clue_band();
+        let mut best = None;
+
+        for _ in 0..max_attempts.max(1) {
+            let solution = SudokuGrid::random_solution(rng);
+            let puzzle = carve(&solution, rng, symmetry, *band.start(), |puzzle| count_solutions(puzzle, 2) == 1 && grade(puzzle) <= difficulty);
+            let clues = puzzle.filled_count();
+            if band.contains(&clues) && grade(&puzzle) == difficulty {
+                return Ok(puzzle)
+            }
+            best = Some(puzzle);
+        }
+
+        Err(GenError::TargetNotMet { best: best.unwrap_or_else(SudokuGrid::empty) })
+    }
+
+    /// Creates a grid with values from an example sudoku.
+    pub fn example_grid() -> SudokuGrid {
+        SudokuGrid::from_cells(&[
+                5, 3, 0,   0, 7, 0,   0, 0, 0,
+                6, 0, 0,   1, 9, 5,   0, 0, 0,
+                0, 9, 8,   0, 0, 0,   0, 6, 0,
+
+                8, 0, 0,   0, 6, 0,   0, 0, 3,
+                4, 0, 0,   8, 0, 3,   0, 0, 1,
+                7, 0, 0,   0, 2, 0,   0, 0, 6,
+
+                0, 6, 0,   0, 0, 0,   2, 8, 0,
+                0, 0, 0,   4, 1, 9,   0, 0, 5,
+                0, 0, 0,   0, 8, 0,   0, 7, 9
+            ])
+    }
+
+    /// Returns the puzzle of the built-in library with the specified name, if any.
+    /// The available names are listed in `BUILTIN_PUZZLES`.
+    pub fn builtin_puzzle(name: &str) -> Option<SudokuGrid> {
+        match name {
+            "example" => Some(SudokuGrid::example_grid()),
+            "example2" => Some(SudokuGrid::from_cells(&[
+                0, 0, 9,   7, 4, 8,   0, 0, 0,
+                7, 0, 0,   0, 0, 0,   0, 0, 0,
+                0, 2, 0,   1, 0, 9,   0, 0, 0,
+
+                0, 0, 7,   0, 0, 0,   2, 4, 0,
+                0, 6, 4,   0, 1, 0,   5, 9, 0,
+                0, 9, 8,   0, 0, 0,   3, 0, 0,
+
+                0, 0, 0,   8, 0, 3,   0, 2, 0,
+                0, 0, 0,   0, 0, 0,   0, 0, 6,
+                0, 0, 0,   2, 7, 5,   9, 0, 0
+            ])),
+            "easy1" => Some(SudokuGrid::from_cells(&[
+                0, 0, 3,   0, 2, 0,   6, 0, 0,
+                9, 0, 0,   3, 0, 5,   0, 0, 1,
+                0, 0, 1,   8, 0, 6,   4, 0, 0,
+
+                0, 0, 8,   1, 0, 2,   9, 0, 0,
+                7, 0, 0,   0, 0, 0,   0, 0, 8,
+                0, 0, 6,   7, 0, 8,   2, 0, 0,
+
+                0, 0, 2,   6, 0, 9,   5, 0, 0,
+                8, 0, 0,   2, 0, 3,   0, 0, 9,
+                0, 0, 5,   0, 1, 0,   3, 0, 0
+            ])),
+            "escargot" => Some(SudokuGrid::from_cells(&[
+                1, 0, 0,   0, 0, 7,   0, 9, 0,
+                0, 3, 0,   0, 2, 0,   0, 0, 8,
+                0, 0, 9,   6, 0, 0,   5, 0, 0,
+
+                0, 0, 5,   3, 0, 0,   9, 0, 0,
+                0, 1, 0,   0, 8, 0,   0, 0, 2,
+                6, 0, 0,   0, 0, 4,   0, 0, 0,
+
+                3, 0, 0,   0, 0, 0,   0, 1, 0,
+                0, 4, 0,   0, 0, 0,   0, 0, 7,
+                0, 0, 7,   0, 0, 0,   3, 0, 0
+            ])),
+            _ => None
+        }
+    }
+
+    /// Creates a grid holding the specified data.
+    /// Returns an error if the data doesn't hold exactly 81 values or if one of them is greater than 9.
+    pub fn from_data(data: &[u8]) -> Result<SudokuGrid, String> {
+        if data.len() != 81 {
+            return Err(format!("a grid holds 81 values, got {}.", data.len()))
+        }
+        if let Some(index) = data.iter().position(|&v| v > 9) {
+            return Err(format!("the value {} at R{}C{} isn't between 0 and 9.", data[index], index / 9 + 1, index % 9 + 1))
+        }
+
+        Ok(SudokuGrid::from_cells(data))
+    }
+
+    /// Creates a grid from pencil marks: the candidates of every cell, indexed by row then column, where bit `i` is set when the digit `i + 1` is a candidate
+    /// (the convention of `candidates_mask()`). A cell with a single candidate holds that digit, and the other cells only accept their candidates,
+    /// which `check()`, and therefore the propagation and the solver, respect.
+    /// Returns an error if a cell has no candidate or a bit above the digit 9, if two placed digits clash, or if a cell only lists digits already placed in its units.
+    pub fn from_candidates(candidates: &[[u16; 9]; 9]) -> Result<SudokuGrid, String> {
+        let mut data = [0; 81];
+        for (y, row) in candidates.iter().enumerate() {
+            for (x, &mask) in row.iter().enumerate() {
+                if mask == 0 || mask > 0x1FF {
+                    return Err(format!("the candidates of {} should be digits from 1 to 9, got {:#b}.", cell_ref(x, y), mask))
+                }
+                if mask.count_ones() == 1 {
+                    data[y * 9 + x] = mask.trailing_zeros() as u8 + 1
+                }
+            }
+        }
+
+        let mut grid = SudokuGrid::from_cells(&data);
+        grid.validate().map_err(|conflict| format!("{}.", conflict))?;
+        // Candidates placed elsewhere in a unit are simply ruled out, like the pencil marks of a naked single's peers
+        if let Some((x, y)) = grid.empty_cells().into_iter().find(|&(x, y)| (1..=9).all(|value| candidates[y][x] & 1 << (value - 1) == 0 || !grid.check(x, y, value))) {
+            return Err(format!("{} only lists digits already placed in its row, column or group.", cell_ref(x, y)))
+        }
+
+        if !grid.is_complete() {
+            grid.pencil_marks = Some(Box::new(std::array::from_fn(|i| candidates[i / 9][i % 9])))
+        }
+        Ok(grid)
+    }
+
+    /// Creates a grid of the specified size (16 for a hexadoku) holding the specified data, with values from 0 to `size`.
+    /// Returns an error if the size isn't a supported square (9 or 16) or if the data doesn't fit the grid.
+    pub fn from_data_with_size(size: usize, data: &[u8]) -> Result<SudokuGrid, String> {
+        if size == 9 {
+            return SudokuGrid::from_data(data)
+        }
+        if size != 16 {
+            return Err(format!("the size of a grid must be 9 or 16, got {}.", size))
+        }
+        if data.len() != size * size {
+            return Err(format!("a grid of size {} holds {} values, got {}.", size, size * size, data.len()))
+        }
+        if let Some(index) = data.iter().position(|&v| v as usize > size) {
+            return Err(format!("the value {} at R{}C{} isn't between 0 and {}.", data[index], index / size + 1, index % size + 1, size))
+        }
+
+        Ok(SudokuGrid::from_sized_cells(size, data))
+    }
+
+    /// Creates an empty grid of the specified size, which must be 9 or 16.
+    pub fn empty_with_size(size: usize) -> Result<SudokuGrid, String> {
+        SudokuGrid::from_data_with_size(size, &vec![0; size * size])
+    }
+
+    /// Creates a 9x9 grid holding data that is known to be valid, such as the data of an existing grid.
+    fn from_cells(data: &[u8]) -> SudokuGrid {
+        SudokuGrid::from_sized_cells(9, data)
+    }
+
+    /// Creates a grid of the specified size holding data that is known to be valid.
+    fn from_sized_cells(size: usize, data: &[u8]) -> SudokuGrid {
+        let box_size = (1..=size).find(|b| b * b == size).unwrap_or(1);
+        let mut grid = SudokuGrid {
+            data: Box::from(data),
+            size,
+            box_size,
+            kind: GridKind::Classic,
+            cages: Box::new([]),
+            pencil_marks: None,
+            regions: None,
+            history: None,
+            masks: vec![0; 3 * size + 2].into_boxed_slice()
+        };
+
+        // Going along the diagonal covers every row and column, and the groups of the diagonal.
+        // The other groups are reached by shifting the cells of the diagonal groups.
+        for i in 0..size {
+            grid.update_masks(i, i);
+        }
+        for group in 0..size {
+            let (group_x, group_y) = (group % box_size, group / box_size);
+            if group_x != group_y {
+                grid.update_masks(group_x * box_size, group_y * box_size)
+            }
+        }
+        grid.update_masks(size - 1, 0);
+
+        grid
+    }
+}
+
+// Grids are serialized as their rows, like in the JSON of the command line.
+#[cfg(feature = "serde")]
+impl serde::Serialize for SudokuGrid {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.rows().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SudokuGrid {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<SudokuGrid, D::Error> {
+        let rows = Vec::<Vec<u8>>::deserialize(deserializer)?;
+        SudokuGrid::from_rows(&rows).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Checks whether the values of a unit (row, column or group) are a permutation of the digits 1 to 9.
+/// The unit may come from a larger grid, in which case the digits go from 1 to the length of the unit.
+pub fn is_complete_unit(values: &[u8]) -> bool {
+    !values.is_empty() && (1..=values.len()).all(|digit| values.contains(&(digit as u8)))
+}
+
+/// Returns the character showing a digit in a grid of the specified size.
+/// 9x9 grids use the digits 1 to 9, while larger grids use the hexadecimal digits from 0 (for 1) to F (for 16), as is usual for hexadoku.
+pub fn digit_symbol(value: u8, size: usize) -> char {
+    if size <= 9 {
+        char::from(b'0' + value)
+    } else {
+        char::from_digit(value as u32 - 1, 16).map(|c| c.to_ascii_uppercase()).unwrap_or('?')
+    }
+}
+
+/// Parses a character shown by `digit_symbol()` back into a digit, or returns `None` if it isn't a digit of a grid of the specified size.
+pub fn parse_digit_symbol(c: char, size: usize) -> Option<u8> {
+    let value = if size <= 9 { c.to_digit(10)? } else { c.to_digit(16)? + 1 };
+    (1..=size as u32).contains(&value).then_some(value as u8)
+}
+
+// Display implementation for SudokuGrid: helps with displaying the grid in the console.
+impl Display for SudokuGrid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.boxed_string(1, |_, symbol| symbol.to_string()))
+    }
+}
+
+/// ANSI escape code of the color of the clues in `to_colored_string()` (bold).
+const CLUE_COLOR: &str = "\x1b[1m";
+/// ANSI escape code of the color of the solved-in digits in `to_colored_string()` (green).
+const SOLVED_COLOR: &str = "\x1b[32m";
+/// ANSI escape code restoring the default color.
+const RESET_COLOR: &str = "\x1b[0m";
+
+impl SudokuGrid {
+    /// Returns the boxed representation of the `Display` implementation, where the clues of `puzzle` are shown in bold and the other digits in green using ANSI escape codes.
+    /// This makes it easy to check that the solver didn't overwrite any clue.
+    pub fn to_colored_string(&self, puzzle: &SudokuGrid) -> String {
+        self.boxed_string(1, |index, symbol| match (puzzle.data.get(index), self.data[index]) {
+            (_, 0) => symbol.to_string(),
+            (Some(&clue), _) if clue != 0 => format!("{}{}{}", CLUE_COLOR, symbol, RESET_COLOR),
+            _ => format!("{}{}{}", SOLVED_COLOR, symbol, RESET_COLOR)
+        })
+    }
+
+    /// Returns the boxed representation of the `Display` implementation, where each empty cell shows its amount of candidates instead of a digit (see `candidates()`),
+    /// so that the most constrained cells stand out. A naked single, whose only candidate can be placed right away, is shown as `*`,
+    /// a cell without any candidate as `!` and a filled cell as `#`.
+    /// The counts are plain decimal numbers whatever the digits of the grid, so the cells of a hexadoku are widened to fit counts up to 16, with a space before each.
+    pub fn to_candidate_counts_string(&self) -> String {
+        let width = if self.size > 9 { 3 } else { 1 };
+        self.boxed_string(width, |index, _| {
+            let text = match self.data[index] {
+                0 => match self.candidates(index % self.size, index / self.size).len() {
+                    0 => String::from("!"),
+                    1 => String::from("*"),
+                    count => count.to_string()
+                },
+                _ => String::from("#")
+            };
+            format!("{:>width$}", text)
+        })
+    }
+
+    /// Lays out the grid in the boxed format, with each cell shown by `cell` from its index and its symbol (`_` for an empty cell).
+    /// `width` is the amount of characters `cell` shows for each cell (ANSI escape codes aside).
+    fn boxed_string(&self, width: usize, cell: impl Fn(usize, char) -> String) -> String {
+        let (size, box_size) = (self.size, self.box_size);
+        // The border spans the cells, the group separators and the margins
+        let border = format!("|{}|\n", "-".repeat(size * width + (box_size - 1) * 3 + 2));
+        let mut s = String::from("\n");
+        s.push_str(&border);
+
+        for row_index in 0..size {
+            s.push_str("| ");
+
+            for cell_index in 0..size {
+                let index = row_index * size + cell_index;
+                let num = self.data.get(index).filter(|&&v| v != 0).map(|&v| digit_symbol(v, size)).unwrap_or('_');
+
+                if cell_index != 0 && cell_index % box_size == 0 {
+                    s.push_str(" | ")
+                }
+
+                s.push_str(&cell(index, num));
+            }
+            s.push_str(" |");
+            s.push('\n');
+
+            if (row_index + 1) % box_size == 0 {
+                s.push_str(&border)
+            }
+        }
+
+        s
+    }
+
+    /// Returns the grid drawn with Unicode box-drawing characters, with heavy lines around the groups and light lines between the other cells.
+    /// Empty cells are blank. Like the `Display` implementation, the drawing starts on a new line.
+    pub fn to_unicode_string(&self) -> String {
+        let (size, box_size) = (self.size, self.box_size);
+        let heavy = |line: usize| line.is_multiple_of(box_size);
+        let mut s = String::from("\n");
+
+        for y in 0..=size {
+            // Horizontal line above the row y (or below the last row)
+            let dash = if heavy(y) { '━' } else { '─' };
+            for x in 0..=size {
+                s.push(box_junction(y, x, size, heavy(y), heavy(x)));
+                if x < size {
+                    s.extend([dash; 3])
+                }
+            }
+            s.push('\n');
+
+            if y == size {
+                break
+            }
+            for x in 0..size {
+                let value = self.get(x, y);
+                let symbol = if value == 0 { ' ' } else { digit_symbol(value, size) };
+                s.push(if heavy(x) { '┃' } else { '│' });
+                s.extend([' ', symbol, ' '])
+            }
+            s.push_str("┃\n");
+        }
+
+        s
+    }
+}
+
+/// Returns the box-drawing character where the horizontal line `y` meets the vertical line `x` of a grid of the specified size (lines numbered from 0 to `size`).
+/// The border is heavy, so only the crossings inside the grid mix light and heavy lines.
+fn box_junction(y: usize, x: usize, size: usize, heavy_horizontal: bool, heavy_vertical: bool) -> char {
+    match (y, x) {
+        (0, 0) => '┏',
+        (0, x) if x == size => '┓',
+        (y, 0) if y == size => '┗',
+        (y, x) if y == size && x == size => '┛',
+        (0, _) => if heavy_vertical { '┳' } else { '┯' },
+        (y, _) if y == size => if heavy_vertical { '┻' } else { '┷' },
+        (_, 0) => if heavy_horizontal { '┣' } else { '┠' },
+        (_, x) if x == size => if heavy_horizontal { '┫' } else { '┨' },
+        _ => match (heavy_horizontal, heavy_vertical) {
+            (false, false) => '┼',
+            (true, false) => '┿',
+            (false, true) => '╂',
+            (true, true) => '╋'
+        }
+    }
+}
+
+// Debug implementation for SudokuGrid: shows the grid on a single line, in the format of `to_line_string()`, to keep logs readable.
+// The line has a character per cell (81 for a 9x9 grid), so it can be pasted back into `--grid`.
+impl Debug for SudokuGrid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_line_string())
+    }
+}
+
+/// Moves made on a grid with `SudokuGrid::set_tracked()`, as `(x, y, old value, new value)` entries, and the moves taken back since the last one.
+#[derive(Clone, Default)]
+struct History {
+    moves: Vec<(usize, usize, u8, u8)>,
+    undone: Vec<(usize, usize, u8, u8)>
+}
+
+// Clone implementation for SudokuGrid: helps with making a copy of an existing grid.
+impl Clone for SudokuGrid {
+    fn clone(&self) -> Self {
+        SudokuGrid {
+            data: self.data.clone(),
+            size: self.size,
+            box_size: self.box_size,
+            kind: self.kind,
+            cages: self.cages.clone(),
+            pencil_marks: self.pencil_marks.clone(),
+            regions: self.regions.clone(),
+            history: self.history.clone(),
+            masks: self.masks.clone()
+        }
+    }
+}
+
+/// Returns the coordinates of the cells of every unit of the grid: the 9 rows, then the 9 columns, then the 9 groups.
+pub fn units() -> Vec<Vec<(usize, usize)>> {
+    units_of_size(9)
+}
+
+/// Returns the coordinates of the cells of every unit of a grid of the specified size, in the same order as `units()`.
+pub fn units_of_size(size: usize) -> Vec<Vec<(usize, usize)>> {
+    let b = (1..=size).find(|b| b * b == size).unwrap_or(1);
+    let rows = (0..size).map(|y| (0..size).map(|x| (x, y)).collect());
+    let columns = (0..size).map(|x| (0..size).map(|y| (x, y)).collect());
+    let groups = (0..size).map(|i| (0..size).map(|j| (i % b * b + j % b, i / b * b + j / b)).collect());
+
+    rows.chain(columns).chain(groups).collect()
+}
+
+/// Formats the coordinates of a cell using the standard RXCY notation (1-based row then column), e.g. `R3C5` for x = 4 and y = 2.
+pub fn cell_ref(x: usize, y: usize) -> String {
+    format!("R{}C{}", y + 1, x + 1)
+}
+
+/// Parses a cell reference in the RXCY notation into (x, y) coordinates.
+/// The letters are case-insensitive and the column may come first (`R3C5`, `r3c5` and `C5R3` are the same cell).
+pub fn parse_cell_ref(reference: &str) -> Option<(usize, usize)> {
+    let reference = reference.trim().to_ascii_uppercase();
+    let captures = Regex::new(r"^(?:R([1-9])C([1-9])|C([1-9])R([1-9]))$").ok()?.captures(&reference)?;
+
+    // Depending on the order, the row and column are either in the first or last two groups
+    let (row, column) = match (captures.get(1), captures.get(2)) {
+        (Some(row), Some(column)) => (row, column),
+        _ => (captures.get(4)?, captures.get(3)?)
+    };
+
+    Some((column.as_str().parse::<usize>().ok()? - 1, row.as_str().parse::<usize>().ok()? - 1))
+}
+
+/// Additional rule that a Sudoku variant puts on top of the classic rules.
+pub trait Constraint {
+    /// Checks that the digits placed in the grid don't break the rule. Cells that are still empty must never cause a rejection.
+    fn check_grid(&self, grid: &SudokuGrid) -> bool;
+}
+
+/// Sandwich Sudoku clues: the sum of the digits placed between the 1 and the 9 of a row or column.
+/// Rows and columns without a clue are `None`.
+pub struct Sandwich {
+    pub rows: [Option<u8>; 9],
+    pub columns: [Option<u8>; 9]
+}
+
+impl Sandwich {
+    /// Parses the 18 sums of a sandwich sidecar file: the 9 row sums followed by the 9 column sums, separated by commas or whitespace.
+    /// A `-` stands for a row or column without a clue.
+    pub fn parse(data: &str) -> Option<Sandwich> {
+        let sums = data
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|token| !token.is_empty())
+            .map(|token| match token {
+                "-" => Some(None),
+                // The largest possible sandwich is 2 + 3 + ... + 8
+                _ => token.parse::<u8>().ok().filter(|&sum| sum <= 35).map(Some)
+            })
+            .collect::<Option<Vec<Option<u8>>>>()?;
+
+        if sums.len() != 18 {
+            return None
+        }
+
+        let mut sandwich = Sandwich { rows: [None; 9], columns: [None; 9] };
+        sandwich.rows.copy_from_slice(&sums[..9]);
+        sandwich.columns.copy_from_slice(&sums[9..]);
+        Some(sandwich)
+    }
+}
+
+/// Cage of a Killer Sudoku: a group of cells whose digits can't repeat and must add up to `sum`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Cage {
+    /// Cells of the cage, as (x, y).
+    pub cells: Vec<(usize, usize)>,
+    pub sum: u32
+}
+
+impl Cage {
+    /// Checks that the digits of the cage don't repeat and that its sum can still be reached by filling its empty cells with distinct unused digits.
+    /// `placed` is a digit to be placed in one of its cells, as (x, y, value), which is checked as if it was already there.
+    fn fits(&self, grid: &SudokuGrid, placed: Option<(usize, usize, u8)>) -> bool {
+        let (mut used, mut total, mut empty) = (0u32, 0, 0);
+
+        for &(x, y) in &self.cells {
+            let value = match placed {
+                Some((placed_x, placed_y, value)) if (placed_x, placed_y) == (x, y) => value,
+                _ => grid.get(x, y)
+            };
+            match value {
+                0 => empty += 1,
+                value if used & 1 << value != 0 => return false,
+                value => {
+                    used |= 1 << value;
+                    total += value as u32
+                }
+            }
+        }
+
+        // The empty cells add up to at least the smallest unused digits, and at most the largest ones
+        let unused: Vec<u32> = (1..=grid.size as u32).filter(|&value| used & 1 << value == 0).collect();
+        if unused.len() < empty {
+            return false
+        }
+        let least: u32 = unused[..empty].iter().sum();
+        let most: u32 = unused[unused.len() - empty..].iter().sum();
+        (total + least..=total + most).contains(&self.sum)
+    }
+}
+
+/// Parses the regions of a Jigsaw Sudoku: 9 lines of 9 characters, where the cells of a region share the same character (such as a digit or a letter).
+/// Blank lines and comment lines starting with `#` are skipped, as well as spaces within the lines. Regions are numbered in the order their character first appears.
+/// Returns `None` if there aren't exactly 9 such lines of 9 cells, or 9 characters used 9 times each.
+pub fn parse_regions(data: &str) -> Option<[[u8; 9]; 9]> {
+    let lines: Vec<Vec<char>> = data
+        .lines()
+        .map(|line| line.chars().filter(|c| !c.is_whitespace()).collect::<Vec<char>>())
+        .filter(|line| !line.is_empty() && line[0] != '#')
+        .collect();
+    if lines.len() != 9 || lines.iter().any(|line| line.len() != 9) {
+        return None
+    }
+
+    let mut symbols: Vec<char> = Vec::new();
+    let mut regions = [[0u8; 9]; 9];
+    for (y, line) in lines.iter().enumerate() {
+        for (x, &c) in line.iter().enumerate() {
+            regions[y][x] = match symbols.iter().position(|&symbol| symbol == c) {
+                Some(region) => region as u8,
+                None => {
+                    symbols.push(c);
+                    symbols.len() as u8 - 1
+                }
+            };
+        }
+    }
+
+    (0..9).all(|region| regions.iter().flatten().filter(|&&r| r == region).count() == 9).then_some(regions)
+}
+
+/// Parses the cages of a Killer Sudoku, one per line: the sum of the cage followed by its cells in the RXCY notation (see `parse_cell_ref()`),
+/// separated by commas or whitespace, e.g. `10 R1C1 R1C2`. Blank lines and comment lines starting with `#` are skipped.
+/// Returns `None` if a line is malformed, a cage has more than 9 cells or a sum above 45, or a cell belongs to several cages.
+pub fn parse_cages(data: &str) -> Option<Vec<Cage>> {
+    let mut cages: Vec<Cage> = Vec::new();
+
+    for line in data.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+        let mut tokens = line.split(|c: char| c == ',' || c.is_whitespace()).filter(|token| !token.is_empty());
+        let sum = tokens.next()?.parse::<u32>().ok().filter(|sum| (1..=45).contains(sum))?;
+        let cells = tokens.map(parse_cell_ref).collect::<Option<Vec<(usize, usize)>>>()?;
+
+        if cells.is_empty() || cells.len() > 9 || cells.iter().enumerate().any(|(i, cell)| cells[..i].contains(cell) || cages.iter().any(|cage| cage.cells.contains(cell))) {
+            return None
+        }
+        cages.push(Cage { cells, sum })
+    }
+
+    Some(cages)
+}
+
+/// Position of the top left corner of each subgrid of a Samurai Sudoku in its 21x21 layout:
+/// the top left, top right, center, bottom left and bottom right grids.
+pub const SAMURAI_OFFSETS: [(usize, usize); 5] = [(0, 0), (12, 0), (6, 6), (0, 12), (12, 12)];
+
+/// Samurai Sudoku: five classic grids laid out in a 21x21 square, where the center grid shares each of its corner groups with one of the other grids.
+/// Cells are addressed in the layout, and a digit placed in a shared group is placed in both of its grids.
+#[derive(Clone)]
+pub struct SamuraiGrid {
+    grids: [SudokuGrid; 5]
+}
+
+impl SamuraiGrid {
+    /// Creates an empty samurai.
+    pub fn empty() -> SamuraiGrid {
+        SamuraiGrid { grids: std::array::from_fn(|_| SudokuGrid::empty()) }
+    }
+
+    /// Returns the five subgrids, in the order of `SAMURAI_OFFSETS`.
+    pub fn subgrids(&self) -> &[SudokuGrid; 5] {
+        &self.grids
+    }
+
+    /// Returns every subgrid holding the cell in column `x` and row `y` of the layout, as the index of the subgrid and the coordinates of the cell in it.
+    /// Cells of the shared groups belong to two subgrids, and cells in the gaps of the layout to none.
+    fn locate(x: usize, y: usize) -> impl Iterator<Item = (usize, usize, usize)> {
+        SAMURAI_OFFSETS.iter()
+            .enumerate()
+            .filter(move |(_, &(left, top))| (left..left + 9).contains(&x) && (top..top + 9).contains(&y))
+            .map(move |(i, &(left, top))| (i, x - left, y - top))
+    }
+
+    /// Returns the coordinates of every cell of the layout that belongs to a subgrid, in row-major order.
+    pub fn cells() -> Vec<(usize, usize)> {
+        (0..21 * 21).map(|i| (i % 21, i / 21)).filter(|&(x, y)| SamuraiGrid::locate(x, y).next().is_some()).collect()
+    }
+
+    /// Returns the value of the cell in column `x` and row `y` of the layout, 0 if it is empty or in a gap of the layout.
+    pub fn get(&self, x: usize, y: usize) -> u8 {
+        SamuraiGrid::locate(x, y).next().map_or(0, |(i, x, y)| self.grids[i].get(x, y))
+    }
+
+    /// Sets the value of the cell in column `x` and row `y` of the layout, in every subgrid holding it. Cells in the gaps of the layout are ignored.
+    pub fn set(&mut self, x: usize, y: usize, value: u8) {
+        for (i, x, y) in SamuraiGrid::locate(x, y) {
+            self.grids[i].set(x, y, value)
+        }
+    }
+
+    /// Checks if a digit can be placed in a cell of the layout, according to the rules of every subgrid holding it (see `SudokuGrid::check()`).
+    pub fn check(&self, x: usize, y: usize, value: u8) -> bool {
+        let mut subgrids = SamuraiGrid::locate(x, y).peekable();
+        subgrids.peek().is_some() && subgrids.all(|(i, x, y)| self.grids[i].check(x, y, value))
+    }
+
+    /// Checks that every subgrid is solved.
+    pub fn is_solved(&self) -> bool {
+        self.grids.iter().all(SudokuGrid::is_solved)
+    }
+
+    /// Parses a samurai in the standard text layout: 21 lines, where the rows of the subgrids hold a digit per cell, with `.` or `0` for an empty cell.
+    /// Lines crossing two subgrids hold 18 cells, lines crossing the three middle grids hold 21 cells and lines crossing only the center grid hold 9 cells.
+    /// The spaces filling the gaps of the layout are optional, since whitespace is skipped. Blank lines and comment lines starting with `#` are skipped too.
+    /// Returns `None` if the lines don't follow the layout or hold any other character.
+    pub fn parse(data: &str) -> Option<SamuraiGrid> {
+        let lines: Vec<Vec<char>> = data
+            .lines()
+            .map(|line| line.chars().filter(|c| !c.is_whitespace()).collect::<Vec<char>>())
+            .filter(|line| !line.is_empty() && line[0] != '#')
+            .collect();
+        if lines.len() != 21 {
+            return None
+        }
+
+        let mut samurai = SamuraiGrid::empty();
+        for (y, line) in lines.iter().enumerate() {
+            let columns: Vec<usize> = (0..21).filter(|&x| SamuraiGrid::locate(x, y).next().is_some()).collect();
+            if line.len() != columns.len() {
+                return None
+            }
+            for (&x, &c) in columns.iter().zip(line) {
+                let value = match c {
+                    '.' => 0,
+                    c => c.to_digit(10)? as u8
+                };
+                samurai.set(x, y, value)
+            }
+        }
+        Some(samurai)
+    }
+}
+
+// Display implementation for SamuraiGrid: the text layout read by `SamuraiGrid::parse()`, with spaces in the gaps.
+impl Display for SamuraiGrid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for y in 0..21 {
+            let line: String = (0..21)
+                .map(|x| match (SamuraiGrid::locate(x, y).next(), self.get(x, y)) {
+                    (None, _) => ' ',
+                    (Some(_), 0) => '.',
+                    (Some(_), value) => (b'0' + value) as char
+                })
+                .collect();
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// Solves a Samurai Sudoku by backtracking over the cells of the layout, always guessing in the cell with the fewest candidates.
+/// Since a digit placed in a shared group goes in both of its subgrids, the subgrids stay consistent with each other.
+/// If the solving gives up after `max_iterations` guesses, the error holds the center subgrid as far as it got.
+pub fn solve_samurai(grid: SamuraiGrid, max_iterations: u32) -> Result<SamuraiGrid, SudokuSolvingError> {
+    if let Some(conflict) = grid.grids.iter().find_map(|subgrid| subgrid.validate().err()) {
+        return Err(SudokuSolvingError::InvalidGrid(Some(conflict)))
+    }
+
+    let cells = SamuraiGrid::cells();
+    let mut solved_grid = grid;
+    let mut iterations = 0;
+    match fill_samurai(&mut solved_grid, &cells, &mut iterations, iteration_cap(max_iterations)) {
+        Some(true) => Ok(solved_grid),
+        Some(false) => Err(SudokuSolvingError::Unsolvable),
+        None => Err(SudokuSolvingError::IterationCountOverflow(Box::new(solved_grid.grids[2].clone())))
+    }
+}
+
+/// Fills the empty cells of a samurai recursively, returning whether it could be solved, or `None` once `max_iterations` guesses were made.
+fn fill_samurai(grid: &mut SamuraiGrid, cells: &[(usize, usize)], iterations: &mut u32, max_iterations: u32) -> Option<bool> {
+    // The empty cell with the fewest candidates leads to the fewest guesses
+    let mut best: Option<((usize, usize), Vec<u8>)> = None;
+    for &(x, y) in cells.iter().filter(|&&(x, y)| grid.get(x, y) == 0) {
+        let candidates: Vec<u8> = (1..=9).filter(|&value| grid.check(x, y, value)).collect();
+        if best.as_ref().is_none_or(|(_, best_candidates)| candidates.len() < best_candidates.len()) {
+            let dead_end = candidates.is_empty();
+            best = Some(((x, y), candidates));
+            if dead_end {
+                break
+            }
+        }
+    }
+
+    let Some(((x, y), candidates)) = best else {
+        return Some(true)
+    };
+    for value in candidates {
+        *iterations += 1;
+        if *iterations > max_iterations {
+            return None
+        }
+        grid.set(x, y, value);
+        if fill_samurai(grid, cells, iterations, max_iterations)? {
+            return Some(true)
+        }
+    }
+    grid.set(x, y, 0);
+    Some(false)
+}
+
+/// Checks a single row or column against its sandwich sum.
+/// As long as the 1 and the 9 aren't both placed, or cells between them are still empty, the line is only rejected if it already exceeds the sum.
+fn check_sandwich_line(values: &[u8], sum: u8) -> bool {
+    let one = values.iter().position(|&v| v == 1);
+    let nine = values.iter().position(|&v| v == 9);
+
+    match (one, nine) {
+        (Some(one), Some(nine)) => {
+            let between = &values[one.min(nine) + 1..one.max(nine)];
+            let placed_sum: u16 = between.iter().map(|&v| v as u16).sum();
+            if between.contains(&0) {
+                placed_sum <= sum as u16
+            } else {
+                placed_sum == sum as u16
+            }
+        },
+        _ => true
+    }
+}
+
+impl Constraint for Sandwich {
+    fn check_grid(&self, grid: &SudokuGrid) -> bool {
+        (0..9).all(|i| {
+            self.rows[i].is_none_or(|sum| check_sandwich_line(&grid.row(i), sum))
+                && self.columns[i].is_none_or(|sum| check_sandwich_line(&grid.column(i), sum))
+        })
+    }
+}
+
+/// Names and descriptions of the puzzles available through `SudokuGrid::builtin_puzzle()`.
+pub const BUILTIN_PUZZLES: [(&str, &str); 4] = [
+    ("example", "a hard-coded example sudoku grid."),
+    ("example2", "a harder example grid that requires guessing."),
+    ("easy1", "an easy grid, solvable with simple deductions."),
+    ("escargot", "'AI Escargot', a famously hard grid.")
+];
+
+/// Digit repeated in a unit, which makes a grid invalid. The cell in column `x` and row `y` holds one of the repeated digits.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Conflict {
+    pub kind: UnitKind,
+    pub x: usize,
+    pub y: usize,
+    pub value: u8
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            UnitKind::Row => write!(f, "digit {} appears twice in row {}", self.value, self.y + 1),
+            UnitKind::Column => write!(f, "digit {} appears twice in column {}", self.value, self.x + 1),
+            UnitKind::Group => write!(f, "digit {} appears twice in the group of {}", self.value, cell_ref(self.x, self.y)),
+            UnitKind::Diagonal => write!(f, "digit {} appears twice in the diagonal of {}", self.value, cell_ref(self.x, self.y)),
+            UnitKind::Cage => write!(f, "digit {} appears twice in the cage of {}", self.value, cell_ref(self.x, self.y)),
+            UnitKind::KnightMove => write!(f, "digit {} appears a knight's move away from {}", self.value, cell_ref(self.x, self.y))
+        }
+    }
+}
+
+/// Enum of the error kinds that the process of solving can encounter.
+pub enum SudokuSolvingError {
+    /// The grid breaks the rules. Holds the repeated digit when the grid is invalid because of one, rather than because it is empty or of an unsupported kind.
+    InvalidGrid(Option<Conflict>),
+    Unsolvable,
+    /// The solving gave up after its maximum amount of iterations. Holds the partially filled grid it got to, to see how far it went.
+    IterationCountOverflow(Box<SudokuGrid>),
+    /// The search needed more nested guesses than allowed by `SolveOptions::max_guess_depth()`.
+    GuessDepthExceeded,
+    /// The grid has more than one solution, which `solve_unique()` rejects.
+    MultipleSolutions,
+    /// The solving gave up after running past the time allowed by `SolveOptions::timeout()`.
+    Timeout
+}
+
+// Display implementation for SudokuSolvingError: helps with displaying the error after it has been caught.
+impl Display for SudokuSolvingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SudokuSolvingError::InvalidGrid(Some(conflict)) => write!(f, "The supplied sudoku grid is invalid: {}!", conflict),
+            SudokuSolvingError::InvalidGrid(None) => f.write_str("The supplied sudoku grid is invalid!"),
+            SudokuSolvingError::Unsolvable => f.write_str("The supplied sudoku is unsolvable!"),
+            SudokuSolvingError::IterationCountOverflow(_) => f.write_str("The solving process was abnormally long and therefore interrupted."),
+            SudokuSolvingError::GuessDepthExceeded => f.write_str("The sudoku couldn't be solved without nesting more guesses than allowed."),
+            SudokuSolvingError::MultipleSolutions => f.write_str("The supplied sudoku has more than one solution!"),
+            SudokuSolvingError::Timeout => f.write_str("The solving process took longer than allowed and was therefore interrupted.")
+        }
+    }
+}
+
+impl SudokuSolvingError {
+    /// Returns the short marker printed in place of a solution in batch mode.
+    pub fn marker(&self) -> &'static str {
+        match self {
+            SudokuSolvingError::InvalidGrid(_) => "INVALID",
+            SudokuSolvingError::Unsolvable => "UNSOLVABLE",
+            SudokuSolvingError::IterationCountOverflow(_) => "OVERFLOW",
+            SudokuSolvingError::GuessDepthExceeded => "DEPTH",
+            SudokuSolvingError::MultipleSolutions => "MULTIPLE",
+            SudokuSolvingError::Timeout => "TIMEOUT"
+        }
+    }
+}
+
+/// Options that tune the solving process.
+pub struct SolveOptions {
+    /// Maximum amount of iterations the solver can take before giving up.
+    max_iterations: u32,
+    /// Whether the preliminary `check_grid()` validation should be skipped.
+    skip_validation: bool,
+    /// Whether logical deductions are propagated before backtracking (on by default).
+    propagate: bool,
+    /// Whether the time spent in each phase of the solving process is measured.
+    measure_timing: bool,
+    /// Maximum amount of nested guesses the search can make, or `None` for an unbounded search.
+    max_guess_depth: Option<usize>,
+    /// Maximum time the search can take, or `None` to only rely on the iteration cap.
+    timeout: Option<Duration>,
+    /// Whether grids with several solutions are rejected with `MultipleSolutions`.
+    require_unique: bool
+}
+
+impl SolveOptions {
+    /// Creates the default options with the specified iteration cap, or without a cap for `UNBOUNDED_ITERATIONS` (0).
+    pub fn new(max_iterations: u32) -> SolveOptions {
+        SolveOptions {
+            max_iterations: iteration_cap(max_iterations),
+            skip_validation: false,
+            propagate: true,
+            measure_timing: false,
+            max_guess_depth: None,
+            timeout: None,
+            require_unique: false
+        }
+    }
+
+    /// Skips the validity scan done before solving, trusting the caller to supply a rule-consistent grid.
+    /// Only use this on trusted input: an invalid grid may then produce a wrong result instead of `InvalidGrid`.
+    pub fn skip_validation(mut self, skip: bool) -> SolveOptions {
+        self.skip_validation = skip;
+        self
+    }
+
+    /// Fills the cells deduced by `propagate()` (singles, intersections, naked pairs and naked triples) before falling back to backtracking.
+    /// This is on by default since it spares most of the search on puzzles a human can solve; turning it off leaves the search to pure backtracking.
+    pub fn propagate(mut self, propagate: bool) -> SolveOptions {
+        self.propagate = propagate;
+        self
+    }
+
+    /// Measures the time spent validating, propagating and searching, which is then reported in the `SolveStats`.
+    /// Timing is off by default so that the solving process doesn't pay for it when it isn't needed.
+    pub fn measure_timing(mut self, measure: bool) -> SolveOptions {
+        self.measure_timing = measure;
+        self
+    }
+
+    /// Caps the amount of nested guesses of the search. Singles are propagated after every guess, so this bounds how far logic helped by limited guessing can get.
+    /// When a branch would need a deeper guess, it is abandoned; if no solution is found, the solving fails with `GuessDepthExceeded` rather than `Unsolvable`.
+    pub fn max_guess_depth(mut self, depth: usize) -> SolveOptions {
+        self.max_guess_depth = Some(depth);
+        self
+    }
+
+    /// Gives up the search with `Timeout` once it has run for longer than `timeout`. The clock starts when the solving does, and is only read every
+    /// `DEADLINE_CHECK_INTERVAL` iterations, so the search may overrun it slightly. The iteration cap still applies on its own.
+    pub fn timeout(mut self, timeout: Duration) -> SolveOptions {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Fails with `MultipleSolutions` if the grid has more than one solution, like `solve_unique()`.
+    /// The solutions are counted within the same iteration cap and timeout as the solving, before the solving starts.
+    pub fn require_unique(mut self, require: bool) -> SolveOptions {
+        self.require_unique = require;
+        self
+    }
+}
+
+impl Default for SolveOptions {
+    fn default() -> Self {
+        SolveOptions::new(MAX_ITERATIONS_DEFAULT)
+    }
+}
+
+/// Function that solves a sudoku grid.
+/// It takes two parameters: the grid to solve and the maximum amount of iterations it can take to solve, where `UNBOUNDED_ITERATIONS` (0) runs it to completion.
+pub fn solve(grid: SudokuGrid, max_iterations: u32) -> Result<SudokuGrid, SudokuSolvingError> {
+    solve_with_options(grid, &SolveOptions::new(max_iterations))
+}
+
+/// Solves the grid like `solve()`, but fails with `MultipleSolutions` if the grid has more than one solution.
+/// Use this to validate puzzles: a proper sudoku has exactly one solution. The counting of the solutions is capped by `max_iterations` too.
+pub fn solve_unique(grid: SudokuGrid, max_iterations: u32) -> Result<SudokuGrid, SudokuSolvingError> {
+    let options = SolveOptions::new(max_iterations);
+    // Counting up to 2 is enough to tell a unique solution from several ones
+    if count_solutions_with_options(&grid, 2, &options)? > 1 {
+        return Err(SudokuSolvingError::MultipleSolutions)
+    }
+
+    solve_with_options(grid, &options)
+}
+
+/// Solves the grid according to the classic rules as well as the additional rules of a variant.
+/// Without any additional rule, this is the same as `solve()`.
+pub fn solve_with_constraints(grid: SudokuGrid, constraints: &[Box<dyn Constraint>], max_iterations: u32) -> Result<SudokuGrid, SudokuSolvingError> {
+    if constraints.is_empty() {
+        return solve(grid, max_iterations)
+    }
+    if !grid.check_grid_with(constraints) {
+        return Err(SudokuSolvingError::InvalidGrid(grid.validate().err()))
+    }
+
+    let mut grid = grid;
+    let mut iterations = 0;
+    search_with_constraints(&mut grid, constraints, &mut iterations, iteration_cap(max_iterations))?;
+    Ok(grid)
+}
+
+/// Recursive part of `solve_with_constraints()`: tries every candidate of the most constrained empty cell, checking the additional rules after each placement.
+fn search_with_constraints(grid: &mut SudokuGrid, constraints: &[Box<dyn Constraint>], iterations: &mut u32, max_iterations: u32) -> Result<(), SudokuSolvingError> {
+    let (x, y, candidates) = match grid.most_constrained_cell() {
+        Some(cell) => cell,
+        None => return Ok(())
+    };
+
+    for value in candidates {
+        *iterations += 1;
+        if *iterations > max_iterations {
+            return Err(SudokuSolvingError::IterationCountOverflow(Box::new(grid.clone())))
+        }
+
+        grid.set(x, y, value);
+        if constraints.iter().all(|constraint| constraint.check_grid(grid)) {
+            match search_with_constraints(grid, constraints, iterations, max_iterations) {
+                Err(SudokuSolvingError::Unsolvable) => {},
+                result => return result
+            }
+        }
+    }
+
+    grid.set(x, y, 0);
+    Err(SudokuSolvingError::Unsolvable)
+}
+
+/// Solves a copy of the grid assuming the specified value is placed at (x, y), which lets callers explore the consequences of a placement ("what if there's a 7 here?").
+/// Fails with `InvalidGrid` if the placement immediately conflicts with the grid, i.e. the cell holds another digit or the value breaks a rule.
+pub fn solve_assuming(grid: &SudokuGrid, x: usize, y: usize, value: u8, max_iterations: u32) -> Result<SudokuGrid, SudokuSolvingError> {
+    let current = grid.get(x, y);
+    if current != 0 && current != value {
+        return Err(SudokuSolvingError::InvalidGrid(None))
+    }
+
+    let mut assumed = grid.clone();
+    assumed.set(x, y, 0);
+    if !(1..=assumed.size as u8).contains(&value) || !assumed.check(x, y, value) {
+        return Err(SudokuSolvingError::InvalidGrid(None))
+    }
+    assumed.set(x, y, value);
+
+    solve(assumed, max_iterations)
+}
+
+/// Solves a sudoku grid using the specified options.
+pub fn solve_with_options(grid: SudokuGrid, options: &SolveOptions) -> Result<SudokuGrid, SudokuSolvingError> {
+    solve_with_progress(grid, options).map_err(|(err, _)| err)
+}
+
+/// Solves a sudoku grid using the specified options.
+/// On failure, the error comes with the furthest-progressed grid the solver reached, which helps seeing how far it got before giving up.
+/// For an invalid grid, this is the supplied grid itself.
+pub fn solve_with_progress(grid: SudokuGrid, options: &SolveOptions) -> Result<SudokuGrid, (SudokuSolvingError, SudokuGrid)> {
+    solve_with_stats(grid, options, &mut SolveStats::default())
+}
+
+/// Solves a grid with Knuth's Algorithm X, using dancing links over the exact cover formulation of Sudoku.
+/// Each of the 729 rows of the matrix places a digit in a cell, and each of the 324 columns is a requirement that must be met exactly once:
+/// every cell holds a digit, and every row, column and group holds every digit.
+/// The search always picks the requirement with the fewest remaining options, so no iteration cap is needed.
+pub fn solve_dlx(grid: SudokuGrid) -> Result<SudokuGrid, SudokuSolvingError> {
+    // The exact cover matrix is built for classic 9x9 grids only
+    if grid.size != 9 || grid.kind != GridKind::Classic || !grid.cages.is_empty() || grid.pencil_marks.is_some() || grid.regions.is_some() || !grid.check_grid() || grid.data.iter().any(|&v| v > 9) {
+        return Err(SudokuSolvingError::InvalidGrid(grid.validate().err()))
+    }
+
+    let mut links = DancingLinks::sudoku();
+    for (i, &value) in grid.data.iter().enumerate() {
+        if value != 0 && !links.select(i * 9 + value as usize - 1) {
+            return Err(SudokuSolvingError::InvalidGrid(grid.validate().err()))
+        }
+    }
+
+    let mut solution = Vec::with_capacity(81);
+    if !links.search(&mut solution) {
+        return Err(SudokuSolvingError::Unsolvable)
+    }
+
+    let mut solved_grid = grid;
+    for choice in solution {
+        let cell = choice / 9;
+        solved_grid.set(cell % 9, cell / 9, (choice % 9) as u8 + 1)
+    }
+
+    Ok(solved_grid)
+}
+
+/// Toroidal doubly linked matrix of Algorithm X. Every node is an index into the vectors.
+/// The node 0 is the root, the nodes 1 to 324 are the column headers and the other nodes are the ones of the matrix rows.
+struct DancingLinks {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    /// Column header of each node.
+    column: Vec<usize>,
+    /// Matrix row (digit placement) of each node.
+    choice: Vec<usize>,
+    /// Amount of nodes left in each column.
+    size: Vec<usize>,
+    /// First node of each matrix row.
+    row_start: Vec<usize>,
+    /// Whether each column has been covered by a selected row.
+    covered: Vec<bool>
+}
+
+impl DancingLinks {
+    /// Builds the matrix of an empty sudoku: the choice `(y * 9 + x) * 9 + digit - 1` meets the requirements of its cell, row, column and group.
+    fn sudoku() -> DancingLinks {
+        let columns = 324;
+        let mut links = DancingLinks {
+            left: (0..=columns).map(|i| if i == 0 { columns } else { i - 1 }).collect(),
+            right: (0..=columns).map(|i| if i == columns { 0 } else { i + 1 }).collect(),
+            up: (0..=columns).collect(),
+            down: (0..=columns).collect(),
+            column: (0..=columns).collect(),
+            choice: vec![0; columns + 1],
+            size: vec![0; columns + 1],
+            row_start: Vec::with_capacity(729),
+            covered: vec![false; columns + 1]
+        };
+
+        for choice in 0..729 {
+            let (cell, digit) = (choice / 9, choice % 9);
+            let (x, y) = (cell % 9, cell / 9);
+            let requirements = [cell, 81 + y * 9 + digit, 162 + x * 9 + digit, 243 + (y / 3 * 3 + x / 3) * 9 + digit];
+
+            let first = links.column.len();
+            links.row_start.push(first);
+            for (k, &requirement) in requirements.iter().enumerate() {
+                let node = first + k;
+                let header = requirement + 1;
+                links.left.push(if k == 0 { first + 3 } else { node - 1 });
+                links.right.push(if k == 3 { first } else { node + 1 });
+                links.up.push(links.up[header]);
+                links.down.push(header);
+                links.column.push(header);
+                links.choice.push(choice);
+
+                let last = links.up[header];
+                links.down[last] = node;
+                links.up[header] = node;
+                links.size[header] += 1;
+            }
+        }
+
+        links
+    }
+
+    /// Removes a column from the header list, along with every row that meets it.
+    fn cover(&mut self, header: usize) {
+        self.covered[header] = true;
+        self.right[self.left[header]] = self.right[header];
+        self.left[self.right[header]] = self.left[header];
+
+        let mut i = self.down[header];
+        while i != header {
+            let mut j = self.right[i];
+            while j != i {
+                self.down[self.up[j]] = self.down[j];
+                self.up[self.down[j]] = self.up[j];
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+
+    /// Puts back a column removed by `cover()`, in the reverse order.
+    fn uncover(&mut self, header: usize) {
+        let mut i = self.up[header];
+        while i != header {
+            let mut j = self.left[i];
+            while j != i {
+                self.size[self.column[j]] += 1;
+                self.down[self.up[j]] = j;
+                self.up[self.down[j]] = j;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+
+        self.right[self.left[header]] = header;
+        self.left[self.right[header]] = header;
+        self.covered[header] = false;
+    }
+
+    /// Selects a choice up front (a given digit) by covering all of its columns.
+    /// Returns false if one of them is already covered, i.e. the choice clashes with a previous one.
+    fn select(&mut self, choice: usize) -> bool {
+        let first = self.row_start[choice];
+        if (first..first + 4).any(|node| self.covered[self.column[node]]) {
+            return false
+        }
+
+        for node in first..first + 4 {
+            self.cover(self.column[node])
+        }
+        true
+    }
+
+    /// Recursively searches for a set of choices meeting every remaining column exactly once, pushing them to `solution`.
+    fn search(&mut self, solution: &mut Vec<usize>) -> bool {
+        if self.right[0] == 0 {
+            return true
+        }
+
+        // Branch on the column with the fewest rows left
+        let mut header = self.right[0];
+        let mut j = self.right[header];
+        while j != 0 {
+            if self.size[j] < self.size[header] {
+                header = j
+            }
+            j = self.right[j];
+        }
+        if self.size[header] == 0 {
+            return false
+        }
+
+        self.cover(header);
+        let mut i = self.down[header];
+        while i != header {
+            solution.push(self.choice[i]);
+            let mut j = self.right[i];
+            while j != i {
+                self.cover(self.column[j]);
+                j = self.right[j];
+            }
+
+            if self.search(solution) {
+                return true
+            }
+
+            let mut j = self.left[i];
+            while j != i {
+                self.uncover(self.column[j]);
+                j = self.left[j];
+            }
+            solution.pop();
+            i = self.down[i];
+        }
+        self.uncover(header);
+
+        false
+    }
+}
+
+/// Solves a grid given as a fixed array of 81 cells (row-major order, 0 for empty cells) and returns the solved cells.
+/// The whole solving path works on fixed arrays and bitmasks of the digits used by each row, column and group, without any heap allocation.
+/// This gives embedding and FFI callers a simple value-in/value-out interface.
+pub fn solve_array(cells: [u8; 81], max_iterations: u32) -> Result<[u8; 81], SudokuSolvingError> {
+    let max_iterations = iteration_cap(max_iterations);
+    let mut rows = [0u16; 9];
+    let mut columns = [0u16; 9];
+    let mut groups = [0u16; 9];
+    // Indices of the empty cells, which are filled in order
+    let mut empty = [0usize; 81];
+    let mut empty_count = 0;
+
+    for (i, &value) in cells.iter().enumerate() {
+        let (x, y) = (i % 9, i / 9);
+        let g = y / 3 * 3 + x / 3;
+        match value {
+            0 => {
+                empty[empty_count] = i;
+                empty_count += 1
+            },
+            1..=9 => {
+                let bit = 1 << (value - 1);
+                let kind = if rows[y] & bit != 0 {
+                    Some(UnitKind::Row)
+                } else if columns[x] & bit != 0 {
+                    Some(UnitKind::Column)
+                } else {
+                    (groups[g] & bit != 0).then_some(UnitKind::Group)
+                };
+                if let Some(kind) = kind {
+                    return Err(SudokuSolvingError::InvalidGrid(Some(Conflict { kind, x, y, value })))
+                }
+                rows[y] |= bit;
+                columns[x] |= bit;
+                groups[g] |= bit
+            },
+            _ => return Err(SudokuSolvingError::InvalidGrid(None))
+        }
+    }
+
+    // Like `check_grid()`, an empty grid is rejected
+    if empty_count == 81 {
+        return Err(SudokuSolvingError::InvalidGrid(None))
+    }
+
+    let mut solved = cells;
+    let mut depth = 0;
+    let mut iterations = 0;
+
+    while depth < empty_count {
+        if iterations == max_iterations {
+            return Err(SudokuSolvingError::IterationCountOverflow(Box::new(SudokuGrid::from_cells(&solved))))
+        }
+        iterations += 1;
+
+        let i = empty[depth];
+        let (x, y) = (i % 9, i / 9);
+        let g = y / 3 * 3 + x / 3;
+
+        // Take back the digit currently placed, if any
+        let current = solved[i];
+        if current != 0 {
+            let bit = !(1 << (current - 1));
+            rows[y] &= bit;
+            columns[x] &= bit;
+            groups[g] &= bit
+        }
+
+        // Try the digits strictly greater than the one that was placed
+        let used = rows[y] | columns[x] | groups[g];
+        match (current + 1..=9).find(|value| used & (1 << (value - 1)) == 0) {
+            Some(value) => {
+                let bit = 1 << (value - 1);
+                rows[y] |= bit;
+                columns[x] |= bit;
+                groups[g] |= bit;
+                solved[i] = value;
+                depth += 1
+            },
+            None => {
+                solved[i] = 0;
+                if depth == 0 {
+                    return Err(SudokuSolvingError::Unsolvable)
+                }
+                depth -= 1
+            }
+        }
+    }
+
+    Ok(solved)
+}
+
+/// Provenance code of a cell that wasn't filled.
+pub const PROVENANCE_EMPTY: u8 = 0;
+/// Provenance code of a cell that was given by the puzzle.
+pub const PROVENANCE_GIVEN: u8 = 1;
+/// Provenance code of a cell that was filled by logical deduction (singles propagation).
+pub const PROVENANCE_DERIVED: u8 = 2;
+/// Provenance code of a cell that was filled by the backtracking search.
+pub const PROVENANCE_GUESSED: u8 = 3;
+
+/// Statistics about the work done by the solver.
+#[derive(Clone, Debug)]
+pub struct SolveStats {
+    /// Number of iterations of the solving loop.
+    pub iterations: u32,
+    /// Number of dead ends the search stepped back from, to change an earlier guess.
+    pub backtracks: u32,
+    /// Largest amount of guesses the search had pending at once.
+    pub max_depth: usize,
+    /// Wall-clock time of the whole solving process, always measured.
+    pub elapsed: Duration,
+    /// Time spent validating the grid, in nanoseconds (only measured when requested by the options).
+    pub validation_ns: u64,
+    /// Time spent propagating singles, in nanoseconds (only measured when requested by the options).
+    pub propagation_ns: u64,
+    /// Time spent backtracking, in nanoseconds (only measured when requested by the options).
+    pub search_ns: u64,
+    /// Provenance code of each cell in row-major order, telling how the cell was determined (see the `PROVENANCE_` constants).
+    /// Cells the solver couldn't settle stay at `PROVENANCE_EMPTY`.
+    pub provenance: [u8; 81]
+}
+
+impl Default for SolveStats {
+    fn default() -> Self {
+        SolveStats {
+            iterations: 0,
+            backtracks: 0,
+            max_depth: 0,
+            elapsed: Duration::ZERO,
+            validation_ns: 0,
+            propagation_ns: 0,
+            search_ns: 0,
+            provenance: [PROVENANCE_EMPTY; 81]
+        }
+    }
+}
+
+// Display implementation for SolveStats: helps with comparing the work done on different grids or with different options.
+impl Display for SolveStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Iterations: {}, backtracks: {}, max depth: {}, elapsed: {:.3}ms.", self.iterations, self.backtracks, self.max_depth, self.elapsed.as_secs_f64() * 1000.0)
+    }
+}
+
+impl SolveStats {
+    /// Gives the specified provenance to the cells that are filled in the grid and have no provenance yet.
+    fn mark_provenance(&mut self, grid: &SudokuGrid, code: u8) {
+        for (provenance, &value) in self.provenance.iter_mut().zip(grid.data.iter()) {
+            if value != 0 && *provenance == PROVENANCE_EMPTY {
+                *provenance = code
+            }
+        }
+    }
+}
+
+/// Returns the nanoseconds elapsed since the start of a measured phase, or 0 if the phase isn't measured.
+fn elapsed_ns(start: Option<Instant>) -> u64 {
+    start.map(|start| start.elapsed().as_nanos() as u64).unwrap_or(0)
+}
+
+/// Solves a sudoku grid like `solve_with_progress()`, recording statistics about the solving process in `stats`.
+pub fn solve_with_stats(grid: SudokuGrid, options: &SolveOptions, stats: &mut SolveStats) -> Result<SudokuGrid, (SudokuSolvingError, SudokuGrid)> {
+    let start = Instant::now();
+    let result = solve_recording_stats(grid, options, stats);
+    stats.elapsed = start.elapsed();
+    result
+}
+
+/// Body of `solve_with_stats()`, which only adds the measure of the elapsed time around it.
+fn solve_recording_stats(grid: SudokuGrid, options: &SolveOptions, stats: &mut SolveStats) -> Result<SudokuGrid, (SudokuSolvingError, SudokuGrid)> {
+    *stats = SolveStats::default();
+
+    stats.mark_provenance(&grid, PROVENANCE_GIVEN);
+    let deadline = options.timeout.map(|timeout| Instant::now() + timeout);
+
+    let validation_start = options.measure_timing.then(Instant::now);
+    // A complete grid doesn't need any search: it is either already solved or invalid
+    if grid.is_complete() {
+        let valid = options.skip_validation || grid.is_solved();
+        stats.validation_ns = elapsed_ns(validation_start);
+        return if valid {
+            Ok(grid)
+        } else {
+            Err((SudokuSolvingError::InvalidGrid(grid.validate().err()), grid))
+        }
+    }
+
+    let valid = options.skip_validation || grid.check_grid();
+    stats.validation_ns = elapsed_ns(validation_start);
+    if !valid {
+        return Err((SudokuSolvingError::InvalidGrid(grid.validate().err()), grid))
+    }
+    if options.require_unique {
+        match count_solutions_with_options(&grid, 2, options) {
+            Ok(count) if count > 1 => return Err((SudokuSolvingError::MultipleSolutions, grid)),
+            Ok(_) => {},
+            Err(err) => return Err((err, grid))
+        }
+    }
+
+    let mut grid = grid;
+    if options.propagate {
+        let propagation_start = options.measure_timing.then(Instant::now);
+        let propagation = propagate(&mut grid);
+        stats.propagation_ns = elapsed_ns(propagation_start);
+        stats.mark_provenance(&grid, PROVENANCE_DERIVED);
+
+        if let Err(err) = propagation {
+            return Err((err, grid))
+        }
+        if grid.is_complete() {
+            return Ok(grid)
+        }
+    }
+
+    let search_start = options.measure_timing.then(Instant::now);
+    let result = match options.max_guess_depth {
+        Some(depth) => search_within_depth(grid, depth, options.max_iterations, deadline, stats),
+        None => backtrack(grid, options.max_iterations, deadline, stats)
+    };
+    stats.search_ns = elapsed_ns(search_start);
+    if let Ok(solved_grid) = &result {
+        stats.mark_provenance(solved_grid, PROVENANCE_GUESSED)
+    }
+
+    result
+}
+
+/// Searches the grid by guessing the candidates of the most constrained cell, propagating singles after every guess, with at most `depth` nested guesses.
+/// On failure, the error comes with the grid after the initial propagation.
+/// The search gives up with `Timeout` once `deadline` has passed, if there is one.
+pub fn search_within_depth(grid: SudokuGrid, depth: usize, max_iterations: u32, deadline: Option<Instant>, stats: &mut SolveStats) -> Result<SudokuGrid, (SudokuSolvingError, SudokuGrid)> {
+    let mut depth_exceeded = false;
+
+    match guess_within_depth(grid.clone(), depth, 0, iteration_cap(max_iterations), deadline, stats, &mut depth_exceeded) {
+        Ok(solved_grid) => Ok(solved_grid),
+        Err(SudokuSolvingError::Unsolvable) if depth_exceeded => Err((SudokuSolvingError::GuessDepthExceeded, grid)),
+        Err(err) => Err((err, grid))
+    }
+}
+
+/// Recursive part of `search_within_depth()`, with `level` guesses already made. `depth_exceeded` is set when a branch is abandoned because it needed a deeper guess.
+fn guess_within_depth(mut grid: SudokuGrid, depth: usize, level: usize, max_iterations: u32, deadline: Option<Instant>, stats: &mut SolveStats, depth_exceeded: &mut bool) -> Result<SudokuGrid, SudokuSolvingError> {
+    propagate_singles(&mut grid)?;
+
+    let (x, y, candidates) = match grid.most_constrained_cell() {
+        Some(cell) => cell,
+        None => return Ok(grid)
+    };
+    if depth == 0 {
+        *depth_exceeded = true;
+        return Err(SudokuSolvingError::Unsolvable)
+    }
+
+    for value in candidates {
+        stats.iterations += 1;
+        if stats.iterations >= max_iterations {
+            return Err(SudokuSolvingError::IterationCountOverflow(Box::new(grid)))
+        }
+        if deadline_passed(deadline, stats.iterations) {
+            return Err(SudokuSolvingError::Timeout)
+        }
+
+        let mut branch = grid.clone();
+        branch.set(x, y, value);
+        stats.max_depth = stats.max_depth.max(level + 1);
+        match guess_within_depth(branch, depth - 1, level + 1, max_iterations, deadline, stats, depth_exceeded) {
+            Err(SudokuSolvingError::Unsolvable) => stats.backtracks += 1,
+            result => return result
+        }
+    }
+
+    Err(SudokuSolvingError::Unsolvable)
+}
+
+/// Fills the empty cells of the grid by backtracking, always branching on the empty cell with the fewest candidates (minimum remaining values).
+/// Every placement or step back counts as an iteration. On failure, the error comes with the furthest-progressed grid that was reached.
+fn backtrack(grid: SudokuGrid, max_iterations: u32, deadline: Option<Instant>, stats: &mut SolveStats) -> Result<SudokuGrid, (SudokuSolvingError, SudokuGrid)> {
+    let mut solved_grid = grid;
+    // Keep a copy of the grid when the solver gets deeper than it has ever been
+    let mut furthest_depth = 0;
+    let mut furthest_grid = solved_grid.clone();
+
+    // Stack of the decisions taken so far: the cell and the candidates that haven't been tried yet, in reverse order so that they're popped in increasing order.
+    let mut decisions: Vec<(usize, usize, Vec<u8>)> = Vec::new();
+    let mut next_cell = match solved_grid.most_constrained_cell() {
+        Some(cell) => Some(cell),
+        None => return Ok(solved_grid)
+    };
+
+    while stats.iterations < max_iterations {
+        stats.iterations += 1;
+        if deadline_passed(deadline, stats.iterations) {
+            return Err((SudokuSolvingError::Timeout, furthest_grid))
+        }
+
+        if let Some((x, y, mut candidates)) = next_cell.take() {
+            candidates.reverse();
+            decisions.push((x, y, candidates));
+        }
+
+        // If there is no decision left to change, every combination has been tried
+        let (x, y, candidates) = match decisions.last_mut() {
+            Some(decision) => decision,
+            None => return Err((SudokuSolvingError::Unsolvable, furthest_grid))
+        };
+
+        match candidates.pop() {
+            Some(value) => {
+                solved_grid.set(*x, *y, value);
+                stats.max_depth = stats.max_depth.max(decisions.len());
+                if decisions.len() > furthest_depth {
+                    furthest_depth = decisions.len();
+                    furthest_grid = solved_grid.clone();
+                }
+
+                match solved_grid.most_constrained_cell() {
+                    None => return Ok(solved_grid),
+                    cell => next_cell = cell
+                }
+            },
+            None => {
+                // Dead end: the cell is emptied and we go back to change the previous decision
+                solved_grid.set(*x, *y, 0);
+                decisions.pop();
+                stats.backtracks += 1;
+            }
+        }
+    }
+
+    // The sudoku couldn't be solved within the allowed amount of iterations
+    Err((SudokuSolvingError::IterationCountOverflow(Box::new(furthest_grid.clone())), furthest_grid))
+}
+
+/// Amount of iterations between two reads of the clock when the search has a deadline, since reading it at every iteration would slow the search down.
+const DEADLINE_CHECK_INTERVAL: u32 = 4096;
+
+/// Tells whether the search should give up because of its deadline. The clock is only read every `DEADLINE_CHECK_INTERVAL` iterations.
+fn deadline_passed(deadline: Option<Instant>, iterations: u32) -> bool {
+    match deadline {
+        Some(deadline) => iterations.is_multiple_of(DEADLINE_CHECK_INTERVAL) && Instant::now() >= deadline,
+        None => false
+    }
+}
+
+/// Iterator of `SudokuGrid::solutions()`. The decisions are kept like in `backtrack()`, so the search can resume after a solution.
+struct Solutions {
+    grid: SudokuGrid,
+    /// Stack of the decisions taken so far: the cell and the candidates that haven't been tried yet, in reverse order.
+    decisions: Vec<(usize, usize, Vec<u8>)>,
+    /// Whether the first decision has been taken, or the search was given up before starting.
+    started: bool
+}
+
+impl Solutions {
+    /// Pushes the decision of the most constrained cell, or returns false if the grid is full.
+    fn decide(&mut self) -> bool {
+        match self.grid.most_constrained_cell() {
+            Some((x, y, mut candidates)) => {
+                candidates.reverse();
+                self.decisions.push((x, y, candidates));
+                true
+            },
+            None => false
+        }
+    }
+}
+
+impl Iterator for Solutions {
+    type Item = SudokuGrid;
+
+    fn next(&mut self) -> Option<SudokuGrid> {
+        if !self.started {
+            self.started = true;
+            // A grid that is already full is its own single solution
+            if !self.decide() {
+                return self.grid.is_solved().then(|| self.grid.clone())
+            }
+        }
+
+        loop {
+            let (x, y, candidates) = self.decisions.last_mut()?;
+            let (x, y) = (*x, *y);
+            match candidates.pop() {
+                Some(value) => {
+                    self.grid.set(x, y, value);
+                    if !self.decide() {
+                        return Some(self.grid.clone())
+                    }
+                },
+                None => {
+                    self.grid.set(x, y, 0);
+                    self.decisions.pop();
+                }
+            }
+        }
+    }
+}
+
+/// Counts the solutions of a sudoku grid, stopping as soon as `limit` solutions have been found.
+/// Passing a limit of 2 is enough to check whether a grid is uniquely solvable.
+pub fn count_solutions(grid: &SudokuGrid, limit: usize) -> usize {
+    if limit == 0 || !grid.check_grid() {
+        return 0
+    }
+
+    let mut grid = grid.clone();
+    let mut count = 0;
+    count_solutions_from(&mut grid, limit, &mut count);
+    count
+}
+
+/// Counts the solutions of a sudoku grid like `count_solutions()`, within the iteration cap and the timeout of `options`.
+/// Fails with `IterationCountOverflow` (holding the grid being counted) or `Timeout` if the counting can't finish in time.
+pub fn count_solutions_with_options(grid: &SudokuGrid, limit: usize, options: &SolveOptions) -> Result<usize, SudokuSolvingError> {
+    if limit == 0 || !grid.check_grid() {
+        return Ok(0)
+    }
+
+    let deadline = options.timeout.map(|timeout| Instant::now() + timeout);
+    let mut grid = grid.clone();
+    let (mut count, mut iterations) = (0, 0);
+    match count_solutions_within(&mut grid, limit, &mut count, &mut iterations, options.max_iterations, deadline) {
+        Ok(()) => Ok(count),
+        Err(SudokuSolvingError::IterationCountOverflow(_)) => Err(SudokuSolvingError::IterationCountOverflow(Box::new(grid))),
+        Err(err) => Err(err)
+    }
+}
+
+/// Recursive part of `count_solutions_with_options()`: `count_solutions_from()` counting its iterations against `max_iterations` and checking `deadline`.
+fn count_solutions_within(grid: &mut SudokuGrid, limit: usize, count: &mut usize, iterations: &mut u32, max_iterations: u32, deadline: Option<Instant>) -> Result<(), SudokuSolvingError> {
+    let (x, y, candidates) = match grid.most_constrained_cell() {
+        Some(cell) => cell,
+        None => {
+            *count += 1;
+            return Ok(())
+        }
+    };
+
+    for value in candidates {
+        *iterations += 1;
+        if *iterations >= max_iterations {
+            return Err(SudokuSolvingError::IterationCountOverflow(Box::new(grid.clone())))
+        }
+        if deadline_passed(deadline, *iterations) {
+            return Err(SudokuSolvingError::Timeout)
+        }
+
+        grid.set(x, y, value);
+        let result = count_solutions_within(grid, limit, count, iterations, max_iterations, deadline);
+        if result.is_err() || *count >= limit {
+            grid.set(x, y, 0);
+            return result
+        }
+    }
+    grid.set(x, y, 0);
+    Ok(())
+}
+
+/// Counts the solutions of a sudoku grid like `count_solutions()`, exploring each candidate of the first cell that branches on a separate thread.
+/// Every branch is counted up to `limit` before the counts are added up, so the result is the same as `count_solutions()` whatever the scheduling of the threads.
+#[cfg(feature = "rayon")]
+pub fn count_solutions_parallel(grid: &SudokuGrid, limit: usize) -> usize {
+    if limit == 0 || !grid.check_grid() {
+        return 0
+    }
+
+    // Cells with a single candidate are filled first, since they don't split the search
+    let mut grid = grid.clone();
+    let (x, y, candidates) = loop {
+        match grid.most_constrained_cell() {
+            None => return 1,
+            Some((x, y, candidates)) if candidates.len() == 1 => grid.set(x, y, candidates[0]),
+            Some(cell) => break cell
+        }
+    };
+
+    let total: usize = candidates
+        .into_par_iter()
+        .map(|value| {
+            let mut branch = grid.clone();
+            branch.set(x, y, value);
+            let mut count = 0;
+            count_solutions_from(&mut branch, limit, &mut count);
+            count
+        })
+        .sum();
+    total.min(limit)
+}
+
+/// Recursive part of `count_solutions()`: branches on every candidate of the most constrained empty cell.
+fn count_solutions_from(grid: &mut SudokuGrid, limit: usize, count: &mut usize) {
+    match grid.most_constrained_cell() {
+        None => *count += 1,
+        Some((x, y, candidates)) => {
+            for value in candidates {
+                grid.set(x, y, value);
+                count_solutions_from(grid, limit, count);
+                if *count >= limit {
+                    break
+                }
+            }
+            grid.set(x, y, 0)
+        }
+    }
+}
+
+/// Finds the single additional clue that most reduces the number of solutions of an ambiguous grid.
+/// Returns the placement (x, y, value) along with the number of solutions left, among the first `limit` solutions found.
+/// Only clues that keep the grid solvable are considered. Returns `None` if the grid already has at most one solution.
+pub fn most_reducing_clue(grid: &SudokuGrid, limit: usize) -> Option<(usize, usize, u8, usize)> {
+    if limit == 0 || !grid.check_grid() {
+        return None
+    }
+
+    let mut solutions = Vec::new();
+    collect_solutions_from(&mut grid.clone(), limit, &mut solutions);
+    if solutions.len() <= 1 {
+        return None
+    }
+
+    // Counts how many of the solutions agree with each possible clue
+    let mut tally = vec![vec![0usize; grid.size]; grid.data.len()];
+    for solution in &solutions {
+        for (i, &value) in solution.data.iter().enumerate() {
+            tally[i][value as usize - 1] += 1;
+        }
+    }
+
+    let mut best: Option<(usize, usize, u8, usize)> = None;
+    for (x, y) in grid.empty_cells() {
+        for (value, &count) in (1..=grid.size as u8).zip(tally[y * grid.size + x].iter()) {
+            if count >= 1 && best.is_none_or(|(_, _, _, best_count)| count < best_count) {
+                best = Some((x, y, value, count));
+            }
+        }
+    }
+
+    best
+}
+
+/// Same as `count_solutions_from()` but keeps every solution found, up to `limit` of them.
+fn collect_solutions_from(grid: &mut SudokuGrid, limit: usize, solutions: &mut Vec<SudokuGrid>) {
+    match grid.most_constrained_cell() {
+        None => solutions.push(grid.clone()),
+        Some((x, y, candidates)) => {
+            for value in candidates {
+                grid.set(x, y, value);
+                collect_solutions_from(grid, limit, solutions);
+                if solutions.len() >= limit {
+                    break
+                }
+            }
+            grid.set(x, y, 0)
+        }
+    }
+}
+
+/// Enum of the symmetries a puzzle can be declared with, or generated with (see `SudokuGrid::generate_symmetric()`).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Symmetry {
+    None,
+    /// The puzzle looks the same after a half-turn rotation.
+    Rotational180,
+    /// The puzzle is mirrored across the middle row, so the top and bottom halves match.
+    Horizontal,
+    /// The puzzle is mirrored across the middle column, so the left and right halves match.
+    Vertical,
+    /// The puzzle is mirrored across the main diagonal, from the top left corner to the bottom right corner.
+    Diagonal
+}
+
+impl Symmetry {
+    /// Returns the index of the cell that the cell at `index` is moved to by the symmetry.
+    pub fn image(&self, index: usize) -> usize {
+        let (x, y) = (index % 9, index / 9);
+        match self {
+            Symmetry::None => index,
+            Symmetry::Rotational180 => 80 - index,
+            Symmetry::Horizontal => (8 - y) * 9 + x,
+            Symmetry::Vertical => y * 9 + 8 - x,
+            Symmetry::Diagonal => x * 9 + y
+        }
+    }
+
+    /// Returns the cell at `index` followed by its image, if the symmetry moves it. Every symmetry is its own inverse, so that is the whole orbit of the cell.
+    /// Cells that the symmetry maps onto themselves, like the center cell of a half turn, have an orbit of one cell.
+    pub fn orbit(&self, index: usize) -> Vec<usize> {
+        match self.image(index) {
+            image if image == index => vec![index],
+            image => vec![index, image]
+        }
+    }
+}
+
+/// Finds a relabeling of the digits (indexed by digit - 1) such that transforming the grid by the symmetry gives back the grid with its digits relabeled.
+/// Returns `None` if the grid doesn't have the symmetry, or isn't 9x9.
+pub fn symmetry_relabeling(grid: &SudokuGrid, symmetry: Symmetry) -> Option<[u8; 9]> {
+    if grid.size != 9 {
+        return None
+    }
+    let mut mapping = [0u8; 9];
+
+    for i in 0..81 {
+        match (grid.data[i], grid.data[symmetry.image(i)]) {
+            (0, 0) => {},
+            (0, _) | (_, 0) => return None,
+            (from, to) => {
+                let mapped = &mut mapping[from as usize - 1];
+                if *mapped == 0 {
+                    *mapped = to
+                } else if *mapped != to {
+                    return None
+                }
+            }
+        }
+    }
+
+    // The mapping must be injective to be completed into a permutation
+    let mut unused: Vec<u8> = (1..=9).filter(|digit| !mapping.contains(digit)).collect();
+    if mapping.iter().filter(|&&to| to != 0).count() + unused.len() != 9 {
+        return None
+    }
+    // Digits absent from the grid can be mapped to any remaining digit
+    for mapped in mapping.iter_mut().filter(|mapped| **mapped == 0) {
+        *mapped = unused.remove(0)
+    }
+
+    Some(mapping)
+}
+
+/// Counts the solutions of a sudoku grid like `count_solutions()`, using a declared symmetry of the grid to prune the search.
+/// If the grid is transformed into itself (up to a relabeling of its digits) by the symmetry, so are its solutions: the solutions where an empty cell that the symmetry maps to itself holds a digit
+/// are in bijection with the solutions where it holds the relabeled digit. Only one digit of each relabeling cycle is then explored for that cell.
+/// Falls back to the plain search if the grid doesn't actually have the symmetry, or if it isn't a classic 9x9 grid without cages, irregular groups or pencil marks,
+/// whose constraints the symmetry may not preserve.
+pub fn count_solutions_with_symmetry(grid: &SudokuGrid, limit: usize, symmetry: Symmetry) -> usize {
+    if limit == 0 || !grid.check_grid() {
+        return 0
+    }
+    if grid.size != 9 || grid.kind != GridKind::Classic || !grid.cages.is_empty() || grid.regions.is_some() || grid.pencil_marks.is_some() {
+        return count_solutions(grid, limit)
+    }
+
+    let fixed_cell = (0..81).find(|&i| symmetry.image(i) == i && grid.data[i] == 0);
+    let (relabeling, index) = match (symmetry_relabeling(grid, symmetry), fixed_cell) {
+        (Some(relabeling), Some(index)) => (relabeling, index),
+        _ => return count_solutions(grid, limit)
+    };
+    let (x, y) = (index % 9, index / 9);
+
+    let mut work = grid.clone();
+    let mut explored = [false; 9];
+    let mut count = 0;
+
+    for value in 1..=9u8 {
+        if explored[value as usize - 1] {
+            continue
+        }
+
+        // Every digit of the cycle leads to the same amount of solutions
+        let mut cycle_length = 0;
+        let mut digit = value;
+        loop {
+            explored[digit as usize - 1] = true;
+            cycle_length += 1;
+            digit = relabeling[digit as usize - 1];
+            if digit == value {
+                break
+            }
+        }
+
+        if work.check(x, y, value) {
+            let mut branch_count = 0;
+            work.set(x, y, value);
+            count_solutions_from(&mut work, limit, &mut branch_count);
+            work.set(x, y, 0);
+
+            count += branch_count * cycle_length;
+            if count >= limit {
+                return limit
+            }
+        }
+    }
+
+    count
+}
+
+/// Fills every empty cell that has a single candidate left (naked single) and returns how many cells were filled.
+/// Fails with `Unsolvable` if an empty cell has no candidate at all.
+fn fill_naked_singles(grid: &mut SudokuGrid) -> Result<usize, SudokuSolvingError> {
+    let mut filled = 0;
+
+    for y in 0..grid.size {
+        for x in 0..grid.size {
+            if grid.get(x, y) == 0 {
+                let candidates = grid.candidates(x, y);
+                match candidates.len() {
+                    0 => return Err(SudokuSolvingError::Unsolvable),
+                    1 => {
+                        grid.set(x, y, candidates[0]);
+                        filled += 1
+                    },
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    Ok(filled)
+}
+
+/// Fills every digit that can only go in a single cell of a unit (hidden single) and returns how many cells were filled.
+/// Fails with `Unsolvable` if a digit missing from a unit can't be placed anywhere in it.
+fn fill_hidden_singles(grid: &mut SudokuGrid) -> Result<usize, SudokuSolvingError> {
+    let mut filled = 0;
+
+    for unit in grid.all_units() {
+        for value in 1..=grid.size as u8 {
+            if unit.iter().any(|&(x, y)| grid.get(x, y) == value) {
+                continue
+            }
+
+            let places: Vec<&(usize, usize)> = unit.iter().filter(|&&(x, y)| grid.get(x, y) == 0 && grid.check(x, y, value)).collect();
+            match places.as_slice() {
+                [] => return Err(SudokuSolvingError::Unsolvable),
+                [&(x, y)] => {
+                    grid.set(x, y, value);
+                    filled += 1
+                },
+                _ => {}
+            }
+        }
+    }
+
+    Ok(filled)
+}
+
+/// Kind of unit a digit is deduced or repeated in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UnitKind {
+    Row,
+    Column,
+    Group,
+    /// One of the main diagonals of an X-Sudoku.
+    Diagonal,
+    /// A cage of a Killer Sudoku.
+    Cage,
+    /// The cells a knight's move away from a cell of an Anti-Knight Sudoku. It isn't a unit that holds every digit, so only conflicts refer to it.
+    KnightMove
+}
+
+/// Logical technique that justifies placing a digit.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Technique {
+    /// The digit is the only candidate left in its cell.
+    NakedSingle,
+    /// The cell is the only place left for the digit in a unit.
+    HiddenSingle(UnitKind)
+}
+
+impl Display for Technique {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Technique::NakedSingle => write!(f, "naked single"),
+            Technique::HiddenSingle(UnitKind::Row) => write!(f, "hidden single in row"),
+            Technique::HiddenSingle(UnitKind::Column) => write!(f, "hidden single in column"),
+            Technique::HiddenSingle(UnitKind::Group) => write!(f, "hidden single in group"),
+            Technique::HiddenSingle(UnitKind::Diagonal) => write!(f, "hidden single in diagonal"),
+            Technique::HiddenSingle(UnitKind::Cage) => write!(f, "hidden single in cage"),
+            Technique::HiddenSingle(UnitKind::KnightMove) => write!(f, "hidden single among knight moves")
+        }
+    }
+}
+
+/// Next logical move returned by `SudokuGrid::hint()`: the digit `value` goes in the cell in column `x` and row `y`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Hint {
+    pub x: usize,
+    pub y: usize,
+    pub value: u8,
+    pub technique: Technique
+}
+
+impl Display for Hint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} goes in {} ({})", self.value, cell_ref(self.x, self.y), self.technique)
+    }
+}
+
+/// Logical technique that justifies removing a candidate, along with the cells it is based on.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Elimination {
+    /// These cells of a unit have only as many candidates between them as there are cells, so these candidates can't go elsewhere in the unit.
+    NakedSubset(Vec<(usize, usize)>),
+    /// The candidates of the digit in a group all lie in these cells of a row or column.
+    Pointing(Vec<(usize, usize)>),
+    /// The candidates of the digit in a row or column all lie in these cells of a group.
+    BoxLine(Vec<(usize, usize)>)
+}
+
+impl Display for Elimination {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (name, cells) = match self {
+            Elimination::NakedSubset(cells) => ("naked", cells),
+            Elimination::Pointing(cells) => ("pointing", cells),
+            Elimination::BoxLine(cells) => ("box-line reduction", cells)
+        };
+        let size = match (self, cells.len()) {
+            (Elimination::BoxLine(_), _) => "",
+            (_, 2) => " pair",
+            (_, 3) => " triple",
+            _ => " subset"
+        };
+        write!(f, "{}{} {}", name, size, cells.iter().map(|&(x, y)| cell_ref(x, y)).collect::<Vec<_>>().join("/"))
+    }
+}
+
+/// Step of the solving narrated by `explain()`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Step {
+    /// A digit placed by a logical technique.
+    Place(Hint),
+    /// The digit `value` is removed from the candidates of the cell in column `x` and row `y`.
+    Eliminate {
+        x: usize,
+        y: usize,
+        value: u8,
+        reason: Elimination
+    },
+    /// No technique applies, so the digit `value` found by backtracking is placed in the cell in column `x` and row `y`.
+    Guess {
+        x: usize,
+        y: usize,
+        value: u8
+    }
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Step::Place(hint) => write!(f, "{} = {} ({})", cell_ref(hint.x, hint.y), hint.value, hint.technique),
+            Step::Eliminate { x, y, value, reason } => write!(f, "{} ≠ {} ({})", cell_ref(*x, *y), value, reason),
+            Step::Guess { x, y, value } => write!(f, "guessing {} = {} (backtracking)", cell_ref(*x, *y), value)
+        }
+    }
+}
+
+/// Repeatedly fills naked and hidden singles until no more progress is made and returns how many cells were filled.
+/// Only legal placements are made. Fails with `Unsolvable` if a contradiction is detected.
+pub fn propagate_singles(grid: &mut SudokuGrid) -> Result<usize, SudokuSolvingError> {
+    let mut total = 0;
+
+    loop {
+        let filled = fill_naked_singles(grid)? + fill_hidden_singles(grid)?;
+        if filled == 0 {
+            return Ok(total)
+        }
+        total += filled
+    }
+}
+
+/// Removes candidates with naked subsets of `n` cells: when `n` cells of a unit have only `n` candidates between them, these digits must go in these cells and are removed from the rest of the unit.
+/// Naked pairs use `n = 2` and naked triples `n = 3`. Returns true if any candidate was removed.
+pub fn eliminate_naked_subsets(candidates: &mut [[u16; 9]; 9], n: usize) -> bool {
+    apply_eliminations(candidates, &naked_subset_eliminations(candidates, n))
+}
+
+/// Returns the candidates removed by `eliminate_naked_subsets()`, as `Step::Eliminate` steps, without removing them.
+fn naked_subset_eliminations(candidates: &[[u16; 9]; 9], n: usize) -> Vec<Step> {
+    let mut steps = Vec::new();
+
+    for unit in units() {
+        let cells: Vec<(usize, usize)> = unit.into_iter().filter(|&(x, y)| candidates[y][x] != 0).collect();
+        // Every subset of the empty cells of the unit is a bitmask over `cells`
+        for subset in 0u16..1 << cells.len() {
+            if subset.count_ones() as usize != n {
+                continue
+            }
+
+            let subset: Vec<(usize, usize)> = (0..cells.len()).filter(|&i| subset & 1 << i != 0).map(|i| cells[i]).collect();
+            let digits = subset.iter().fold(0, |digits, &(x, y)| digits | candidates[y][x]);
+            if digits.count_ones() as usize != n {
+                continue
+            }
+
+            for &(x, y) in cells.iter().filter(|cell| !subset.contains(cell)) {
+                push_eliminations(&mut steps, x, y, candidates[y][x] & digits, Elimination::NakedSubset(subset.clone()))
+            }
+        }
+    }
+
+    steps
+}
+
+/// Adds a `Step::Eliminate` for every digit of the bitmask `digits` removed from the cell in column `x` and row `y`, unless the same removal is already in `steps`.
+fn push_eliminations(steps: &mut Vec<Step>, x: usize, y: usize, digits: u16, reason: Elimination) {
+    for value in (1..=9).filter(|value| digits & 1 << (value - 1) != 0) {
+        if !steps.iter().any(|step| matches!(step, Step::Eliminate { x: step_x, y: step_y, value: step_value, .. } if (*step_x, *step_y, *step_value) == (x, y, value))) {
+            steps.push(Step::Eliminate { x, y, value, reason: reason.clone() })
+        }
+    }
+}
+
+/// Removes the candidates of the `Step::Eliminate` steps from a candidate grid. Returns true if there was any.
+fn apply_eliminations(candidates: &mut [[u16; 9]; 9], steps: &[Step]) -> bool {
+    let mut eliminated = false;
+    for step in steps {
+        if let Step::Eliminate { x, y, value, .. } = *step {
+            candidates[y][x] &= !(1 << (value - 1));
+            eliminated = true
+        }
+    }
+    eliminated
+}
+
+/// Removes candidates at the intersections of groups with rows and columns. Returns true if any candidate was removed.
+/// - Pointing pairs and triples: when the candidates of a digit in a group all lie in one row or column, the digit is removed from the rest of that row or column.
+/// - Box-line reduction: when the candidates of a digit in a row or column all lie in one group, the digit is removed from the rest of that group.
+pub fn eliminate_intersections(candidates: &mut [[u16; 9]; 9]) -> bool {
+    apply_eliminations(candidates, &intersection_eliminations(candidates))
+}
+
+/// Returns the candidates removed by `eliminate_intersections()`, as `Step::Eliminate` steps, without removing them.
+fn intersection_eliminations(candidates: &[[u16; 9]; 9]) -> Vec<Step> {
+    let units = units();
+    let (lines, groups) = units.split_at(18);
+    let mut steps = Vec::new();
+
+    for group in groups {
+        for line in lines.iter().filter(|line| line.iter().any(|cell| group.contains(cell))) {
+            for digit in (0..9).map(|i| 1u16 << i) {
+                let has_digit = |&&(x, y): &&(usize, usize)| candidates[y][x] & digit != 0;
+                let in_group: Vec<_> = group.iter().filter(has_digit).collect();
+                let in_line: Vec<_> = line.iter().filter(has_digit).collect();
+
+                // Whichever unit has the digit confined to the intersection forbids it in the rest of the other
+                let (others, reason): (Vec<_>, _) = if !in_group.is_empty() && in_group.iter().all(|cell| line.contains(cell)) {
+                    (in_line.iter().filter(|cell| !group.contains(cell)).collect(), Elimination::Pointing(in_group.into_iter().copied().collect()))
+                } else if !in_line.is_empty() && in_line.iter().all(|cell| group.contains(cell)) {
+                    (in_group.iter().filter(|cell| !line.contains(cell)).collect(), Elimination::BoxLine(in_line.into_iter().copied().collect()))
+                } else {
+                    continue
+                };
+
+                for &&(x, y) in others {
+                    push_eliminations(&mut steps, x, y, digit, reason.clone())
+                }
+            }
+        }
+    }
+
+    steps
+}
+
+/// Returns the placements forced by a candidate grid: cells with a single candidate, and digits with a single place left in a unit.
+/// Fails with `Unsolvable` if an empty cell has no candidate left or a digit missing from a unit can't go anywhere in it.
+fn forced_placements(grid: &SudokuGrid, candidates: &[[u16; 9]; 9]) -> Result<Vec<(usize, usize, u8)>, SudokuSolvingError> {
+    let mut placements = Vec::new();
+
+    for (x, y) in grid.empty_cells() {
+        match candidates[y][x] {
+            0 => return Err(SudokuSolvingError::Unsolvable),
+            mask if mask.count_ones() == 1 => placements.push((x, y, mask.trailing_zeros() as u8 + 1)),
+            _ => {}
+        }
+    }
+
+    for unit in grid.all_units() {
+        for value in 1..=9 {
+            if unit.iter().any(|&(x, y)| grid.get(x, y) == value) {
+                continue
+            }
+
+            match unit.iter().filter(|&&(x, y)| candidates[y][x] & 1 << (value - 1) != 0).collect::<Vec<_>>().as_slice() {
+                [] => return Err(SudokuSolvingError::Unsolvable),
+                [&(x, y)] => placements.push((x, y, value)),
+                _ => {}
+            }
+        }
+    }
+
+    Ok(placements)
+}
+
+/// Propagates singles like `propagate_singles()`, then removes candidates with pointing pairs, box-line reductions, naked pairs and naked triples to find more singles,
+/// until no more progress is made. Returns how many cells were filled. Grids that aren't 9x9 only get their singles propagated. Fails with `Unsolvable` if a contradiction is detected.
+pub fn propagate(grid: &mut SudokuGrid) -> Result<usize, SudokuSolvingError> {
+    propagate_with_techniques(grid, true, 3)
+}
+
+/// Propagation of `propagate()`, only removing candidates at intersections if `intersections` is set, and with naked subsets of at most `max_subset` cells.
+fn propagate_with_techniques(grid: &mut SudokuGrid, intersections: bool, max_subset: usize) -> Result<usize, SudokuSolvingError> {
+    let mut total = propagate_singles(grid)?;
+    // The candidate eliminations only know the square groups
+    if grid.size != 9 || grid.regions.is_some() || (!intersections && max_subset < 2) {
+        return Ok(total)
+    }
+
+    loop {
+        let mut candidates = grid.candidate_bitmasks();
+        while (intersections && eliminate_intersections(&mut candidates)) | (2..=max_subset).fold(false, |eliminated, n| eliminate_naked_subsets(&mut candidates, n) | eliminated) {}
+
+        let placements = forced_placements(grid, &candidates)?;
+        if placements.is_empty() {
+            return Ok(total)
+        }
+
+        for (x, y, value) in placements {
+            match grid.get(x, y) {
+                // The same placement may be forced several times
+                current if current == value => {},
+                0 if grid.check(x, y, value) => {
+                    grid.set(x, y, value);
+                    total += 1
+                },
+                _ => return Err(SudokuSolvingError::Unsolvable)
+            }
+        }
+        total += propagate_singles(grid)?;
+    }
+}
+
+/// Solves a 9x9 grid step by step like a human would, and returns every step taken in order along with the solved grid.
+/// Singles are placed first, then candidates are removed with intersections and naked pairs and triples. When nothing applies, the digit found by backtracking is placed in the most constrained cell.
+/// Fails like `solve()` if the grid can't be solved, or with `InvalidGrid` if it isn't 9x9.
+pub fn explain(grid: &SudokuGrid) -> Result<(Vec<Step>, SudokuGrid), SudokuSolvingError> {
+    if grid.size != 9 {
+        return Err(SudokuSolvingError::InvalidGrid(None))
+    }
+    let solution = solve(grid.clone(), MAX_ITERATIONS_DEFAULT)?;
+
+    let mut grid = grid.clone();
+    let mut candidates = grid.candidate_bitmasks();
+    let mut steps = Vec::new();
+
+    while !grid.is_complete() {
+        // The digits placed since the last step restrict the candidates further
+        let placed = grid.candidate_bitmasks();
+        candidates = std::array::from_fn(|y| std::array::from_fn(|x| candidates[y][x] & placed[y][x]));
+
+        if let Some(hint) = grid.hint_with(|x, y, value| candidates[y][x] & 1 << (value - 1) != 0) {
+            grid.set(hint.x, hint.y, hint.value);
+            steps.push(Step::Place(hint));
+            continue
+        }
+
+        // The candidate eliminations only know the square groups, so a Jigsaw Sudoku goes from singles to guesses
+        let eliminations = [intersection_eliminations(&candidates), naked_subset_eliminations(&candidates, 2), naked_subset_eliminations(&candidates, 3)]
+            .into_iter()
+            .find(|eliminations| grid.regions.is_none() && !eliminations.is_empty());
+        match eliminations {
+            Some(eliminations) => {
+                apply_eliminations(&mut candidates, &eliminations);
+                steps.extend(eliminations)
+            },
+            None => {
+                let (x, y) = grid.empty_cells().into_iter().min_by_key(|&(x, y)| candidates[y][x].count_ones()).unwrap_or_default();
+                let value = solution.get(x, y);
+                grid.set(x, y, value);
+                steps.push(Step::Guess { x, y, value })
+            }
+        }
+    }
+
+    Ok((steps, grid))
+}
+
+/// Enum of the errors that the generation of puzzles can encounter.
+pub enum GenError {
+    /// The requested amount of clues can't lead to a sensible puzzle.
+    InvalidClueCount(usize),
+    /// The generation gave up after its maximum amount of attempts. Holds the puzzle that came the closest to the target.
+    TargetNotMet {
+        best: SudokuGrid
+    }
+}
+
+// Display implementation for GenError: helps with displaying the error after it has been caught.
+impl Display for GenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenError::InvalidClueCount(clues) if *clues < MIN_UNIQUE_CLUES => write!(f, "Can't generate a puzzle with {} clues: a uniquely solvable sudoku needs at least {} clues.", clues, MIN_UNIQUE_CLUES),
+            GenError::InvalidClueCount(clues) => write!(f, "Can't generate a puzzle with {} clues: a puzzle has at most 80 clues, as 81 clues make a solved grid.", clues),
+            GenError::TargetNotMet { .. } => f.write_str("Couldn't generate a puzzle meeting the target within the maximum amount of attempts.")
+        }
+    }
+}
+
+/// Attempts of `SudokuGrid::try_generate_symmetric()` in the program and the WebAssembly entry points.
+/// A symmetric expert puzzle may take several hundred attempts, the other targets far fewer.
+pub const GENERATION_ATTEMPTS_DEFAULT: usize = 5000;
+
+/// Smallest amount of clues of a uniquely solvable sudoku: it has been proven that no 16-clue puzzle has a unique solution.
+pub const MIN_UNIQUE_CLUES: usize = 17;
+
+/// Checks that a puzzle with the specified amount of clues can be generated.
+/// Below 17 clues a puzzle can't be uniquely solvable, and 81 clues would make a solved grid rather than a puzzle.
+pub fn validate_clue_count(clues: usize) -> Result<(), GenError> {
+    if (MIN_UNIQUE_CLUES..=80).contains(&clues) {
+        Ok(())
+    } else {
+        Err(GenError::InvalidClueCount(clues))
+    }
+}
+
+/// Returns an endless stream of uniquely solvable puzzles with the specified amount of clues.
+/// The puzzles are generated lazily, each one advancing a RNG seeded with `seed`: the same seed always yields the same stream.
+/// Every puzzle gets `max_attempts` attempts, and is the result of `SudokuGrid::generate_with_clues()`: a puzzle that runs out of them is yielded as `GenError::TargetNotMet`,
+/// and every item is `GenError::InvalidClueCount` if the clue count can't lead to a sensible puzzle.
+pub fn random_puzzles(clues: usize, seed: u64, max_attempts: usize) -> impl Iterator<Item = Result<SudokuGrid, GenError>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    std::iter::repeat_with(move || SudokuGrid::generate_with_clues(clues, &mut rng, max_attempts))
+}
+
+/// Solves a grid using logical deductions only (singles, intersections, naked pairs and naked triples), without any guessing.
+/// Returns `None` if the deductions get stuck before completing the grid, or if the grid is contradictory.
+pub fn solve_logical_only(grid: &SudokuGrid) -> Option<SudokuGrid> {
+    let mut solved_grid = grid.clone();
+    propagate(&mut solved_grid).ok()?;
+
+    if solved_grid.is_complete() {
+        Some(solved_grid)
+    } else {
+        None
+    }
+}
+
+/// Fills all the empty cells of the grid with randomly ordered candidates, backtracking when needed.
+/// Returns false if the grid can't be completed.
+fn fill_randomly(grid: &mut SudokuGrid, rng: &mut impl Rng) -> bool {
+    match grid.most_constrained_cell() {
+        None => true,
+        Some((x, y, mut candidates)) => {
+            candidates.shuffle(rng);
+            for value in candidates {
+                grid.set(x, y, value);
+                if fill_randomly(grid, rng) {
+                    return true
+                }
+            }
+            grid.set(x, y, 0);
+            false
+        }
+    }
+}
+
+/// Removes the clues of a complete grid in random order, keeping each removal only if `keep` still accepts the resulting puzzle.
+/// The clues are removed together with the cells of their orbit under the specified symmetry, so that the remaining clues follow it.
+/// The removal stops once the puzzle is down to `min_clues` clues, and orbits that would take it below are kept.
+fn carve(solution: &SudokuGrid, rng: &mut impl Rng, symmetry: Symmetry, min_clues: usize, keep: impl Fn(&SudokuGrid) -> bool) -> SudokuGrid {
+    let mut puzzle = solution.clone();
+    let mut clues = 81;
+    let mut cells: Vec<usize> = (0..81).collect();
+    cells.shuffle(rng);
+
+    for i in cells {
+        if clues <= min_clues {
+            break
+        }
+
+        // The cell may already be gone with the orbit of a previous cell
+        let orbit = symmetry.orbit(i);
+        if puzzle.data[i] == 0 || clues - orbit.len() < min_clues {
+            continue
+        }
+
+        for &j in &orbit {
+            puzzle.set(j % 9, j / 9, 0)
+        }
+        if keep(&puzzle) {
+            clues -= orbit.len()
+        } else {
+            for &j in &orbit {
+                puzzle.set(j % 9, j / 9, solution.data[j])
+            }
+        }
+    }
+
+    puzzle
+}
+
+/// Enum of the difficulty levels a puzzle can be rated with, from the easiest to the hardest.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert
+}
+
+impl Difficulty {
+    /// Returns the range of clue counts of the puzzles generated with this difficulty by `SudokuGrid::generate()`.
+    pub fn clue_band(&self) -> std::ops::RangeInclusive<usize> {
+        match self {
+            Difficulty::Easy => 36..=45,
+            Difficulty::Medium => 30..=35,
+            Difficulty::Hard => 25..=29,
+            Difficulty::Expert => MIN_UNIQUE_CLUES..=24
+        }
+    }
+}
+
+// Display implementation for Difficulty: helps with printing the rating of a puzzle.
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Difficulty::Easy => f.write_str("easy"),
+            Difficulty::Medium => f.write_str("medium"),
+            Difficulty::Hard => f.write_str("hard"),
+            Difficulty::Expert => f.write_str("expert")
+        }
+    }
+}
+
+/// Rates a puzzle according to the hardest technique required to solve it, assuming the puzzle is solvable:
+/// - Easy: naked and hidden singles only
+/// - Medium: pointing pairs, box-line reductions and naked pairs
+/// - Hard: naked triples
+/// - Expert: guessing
+pub fn grade(grid: &SudokuGrid) -> Difficulty {
+    for (intersections, max_subset, difficulty) in [(false, 0, Difficulty::Easy), (true, 2, Difficulty::Medium), (true, 3, Difficulty::Hard)] {
+        let mut work = grid.clone();
+        if propagate_with_techniques(&mut work, intersections, max_subset).is_ok() && work.is_complete() {
+            return difficulty
+        }
+    }
+
+    Difficulty::Expert
+}
+
+/// Report of the solving of a batch of puzzles.
+#[derive(Clone, Debug)]
+pub struct BatchReport {
+    /// Number of puzzles in the batch.
+    pub total: usize,
+    /// Number of puzzles that were solved.
+    pub solved: usize,
+    /// Number of puzzles that couldn't be solved.
+    pub failed: usize,
+    /// Sum of the iterations taken by every puzzle.
+    pub total_iterations: u64,
+    /// Average number of iterations taken by a puzzle.
+    pub avg_iterations: f64,
+    /// Highest number of iterations taken by a single puzzle.
+    pub max_iterations: u32,
+    /// Time taken to solve the whole batch.
+    pub elapsed: Duration
+}
+
+/// Solves every puzzle of a batch with the same options.
+/// Returns the result of each puzzle in the order of the batch, along with a report of the batch statistics.
+pub fn solve_batch(puzzles: impl IntoIterator<Item = SudokuGrid>, options: &SolveOptions) -> (Vec<Result<SudokuGrid, SudokuSolvingError>>, BatchReport) {
+    let mut results = Vec::new();
+    let report = solve_batch_with(puzzles.into_iter().map(Ok::<SudokuGrid, Infallible>), options, |result| {
+        match result {
+            Ok(result) => results.push(result),
+            Err(never) => match never {}
+        }
+        true
+    });
+
+    (results, report)
+}
+
+/// Solves the puzzles of a batch one at a time like `solve_batch()`, but hands each result over to `handle` as soon as it is known instead of collecting them,
+/// so that a batch of any length can be streamed. Puzzles that couldn't be read are given as errors, which are passed on to `handle` as the outer error and count as failures.
+/// The batch stops early when `handle` returns false. Returns the report of the puzzles handled so far.
+pub fn solve_batch_with<E>(puzzles: impl IntoIterator<Item = Result<SudokuGrid, E>>, options: &SolveOptions, mut handle: impl FnMut(Result<Result<SudokuGrid, SudokuSolvingError>, E>) -> bool) -> BatchReport {
+    let start = Instant::now();
+    let mut report = BatchReport::new();
+
+    for puzzle in puzzles {
+        let mut stats = SolveStats::default();
+        let result = puzzle.map(|puzzle| solve_with_stats(puzzle, options, &mut stats).map_err(|(err, _)| err));
+        report.record(matches!(result, Ok(Ok(_))), stats.iterations);
+        if !handle(result) {
+            break
+        }
+    }
+
+    report.finish(start);
+    report
+}
+
+impl BatchReport {
+    /// Creates the report of an empty batch.
+    pub fn new() -> BatchReport {
+        BatchReport {
+            total: 0,
+            solved: 0,
+            failed: 0,
+            total_iterations: 0,
+            avg_iterations: 0.0,
+            max_iterations: 0,
+            elapsed: Duration::ZERO
+        }
+    }
+
+    /// Records the outcome of a puzzle of the batch.
+    pub fn record(&mut self, solved: bool, iterations: u32) {
+        self.total += 1;
+        if solved {
+            self.solved += 1
+        } else {
+            self.failed += 1
+        }
+        self.total_iterations += iterations as u64;
+        self.max_iterations = self.max_iterations.max(iterations);
+    }
+
+    /// Computes the average iterations and the elapsed time once the batch is over.
+    pub fn finish(&mut self, start: Instant) {
+        if self.total > 0 {
+            self.avg_iterations = self.total_iterations as f64 / self.total as f64
+        }
+        self.elapsed = start.elapsed();
+    }
+}
+
+impl Default for BatchReport {
+    fn default() -> Self {
+        BatchReport::new()
+    }
+}
+
+// Display implementation for BatchReport: helps with printing the summary of a batch.
+impl Display for BatchReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Solved {}/{} puzzles ({} failed) in {:.3}s. Iterations: {} in total, {:.1} on average, {} at most.",
+               self.solved, self.total, self.failed, self.elapsed.as_secs_f64(), self.total_iterations, self.avg_iterations, self.max_iterations)
+    }
+}
+
+pub const MAX_ITERATIONS_DEFAULT: u32 = 1000000;
+
+/// Iteration cap of the solving functions meaning that the solving runs to completion instead of giving up.
+/// Genuine puzzles are solved quickly anyway, but a crafted grid (such as a nearly empty grid without any solution) may keep the solver busy for hours.
+pub const UNBOUNDED_ITERATIONS: u32 = 0;
+
+/// Returns the iterations allowed by the `max_iterations` argument of a solving function, where `UNBOUNDED_ITERATIONS` lifts the cap.
+/// The iterations are counted on 32 bits, so an unbounded solving still stops after `u32::MAX` of them, which takes hours.
+fn iteration_cap(max_iterations: u32) -> u32 {
+    match max_iterations {
+        UNBOUNDED_ITERATIONS => u32::MAX,
+        max_iterations => max_iterations
+    }
+}
+
+/// Splits a line of CSV into its fields. A field may be quoted, in which case it can hold commas, and a doubled quote stands for a quote.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().unwrap();
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            },
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => field.push(c)
+        }
+    }
+
+    fields
+}
+
+/// Checks whether data looks like a CSV grid: 9 non-blank lines, each holding commas.
+/// The flat format also separates its values with commas, but on a single line.
+fn looks_like_csv(data: &str) -> bool {
+    let lines: Vec<&str> = data.lines().filter(|line| !line.trim().is_empty()).collect();
+    lines.len() == 9 && lines.iter().all(|line| line.contains(','))
+}
+
+/// Parses the grid info supplied by the user, which can be the name of a template, direct data or the path of a file holding the data.
+pub fn parse_grid_info(info: &str) -> Result<SudokuGrid, ParseError> {
+    // We first check for templates
+    match info {
+        "random" => Ok(SudokuGrid::valid_random()),
+        name => {
+            if let Some(grid) = SudokuGrid::builtin_puzzle(name) {
+                return Ok(grid)
+            }
+
+            // Then for a grid copied from the boxed output of the program
+            if let Some(grid) = parse_boxed_grid(info) {
+                return Ok(grid)
+            }
+
+            // Then for a file, whose content goes through the same parsing as direct data
+            let path = std::path::Path::new(info.trim());
+            let data = match path.is_file() {
+                true => read_puzzle_file(info.trim()).unwrap_or_default(),
+                false => info.to_string()
+            };
+
+            // CSV files of spreadsheets hold a row per line
+            if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("csv")) || looks_like_csv(&data) {
+                return SudokuGrid::from_csv(&data).map_err(ParseError::InvalidCsv)
+            }
+
+            // Pencil marks list the candidates of every cell
+            if let Some(candidates) = parse_candidates(&data) {
+                return SudokuGrid::from_candidates(&candidates).map_err(ParseError::InconsistentCandidates)
+            }
+
+            // The SDK format may hold comments, which aren't cells
+            parse_sdk_grid(&data).map_or_else(|| parse_grid(&data), Ok)
+        }
+    }
+}
+
+/// Parses the grid info of a grid of the specified size, which can be direct data or the path of a file holding the data.
+/// 9x9 grids accept everything `parse_grid_info()` does. Other sizes accept a digit symbol (see `digit_symbol()`) or `.` per cell, or numbers separated by commas, and ignore whitespace so that the cells can be laid out on several lines.
+pub fn parse_sized_grid_info(info: &str, size: usize) -> Result<SudokuGrid, ParseError> {
+    if size == 9 {
+        return parse_grid_info(info)
+    }
+
+    let data = if std::path::Path::new(info).is_file() { read_data_from_file(info).unwrap_or_default() } else { info.to_string() };
+    let cells: Vec<(usize, char)> = data.chars().enumerate().map(|(i, c)| (i + 1, c)).filter(|(_, c)| !c.is_whitespace()).collect();
+
+    let values = if data.contains(',') {
+        // An empty number is an empty cell, and an invalid number is reported by its first character
+        cells
+            .split(|&(_, c)| c == ',')
+            .map(|number| match number.first() {
+                None => Ok(0),
+                Some(&(position, c)) => number.iter().map(|(_, c)| c).collect::<String>().parse().ok()
+                    .filter(|&value: &u8| value as usize <= size)
+                    .ok_or(ParseError::InvalidChar { c, position })
+            })
+            .collect::<Result<Vec<u8>, ParseError>>()?
+    } else {
+        cells
+            .iter()
+            .map(|&(position, c)| if c == '.' { Ok(0) } else { parse_digit_symbol(c, size).ok_or(ParseError::InvalidChar { c, position }) })
+            .collect::<Result<Vec<u8>, ParseError>>()?
+    };
+
+    SudokuGrid::from_data_with_size(size, &values).map_err(|_| ParseError::CellCount { found: values.len(), expected: size * size })
+}
+
+/// Error of the parsing of grid data.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    /// The data doesn't hold the amount of cells of the grid.
+    CellCount {
+        found: usize,
+        expected: usize
+    },
+    /// The data holds a character that is neither a digit, an empty cell marker nor a separator. The position is 1-based and counts every character of the data.
+    InvalidChar {
+        c: char,
+        position: usize
+    },
+    /// The pencil marks contradict each other, as explained by the message of `SudokuGrid::from_candidates()`.
+    InconsistentCandidates(String),
+    /// The CSV data isn't 9 rows of 9 digits or blank cells, as explained by the message of `SudokuGrid::from_csv()`.
+    InvalidCsv(String)
+}
+
+// Display implementation for ParseError: helps with displaying the error after it has been caught.
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::CellCount { found, expected } => write!(f, "found {} cells, expected {}", found, expected),
+            ParseError::InvalidChar { c, position } => write!(f, "invalid char '{}' at position {}", c, position),
+            ParseError::InconsistentCandidates(message) => write!(f, "inconsistent pencil marks: {}", message.trim_end_matches('.')),
+            ParseError::InvalidCsv(message) => write!(f, "invalid CSV grid: {}", message.trim_end_matches('.'))
+        }
+    }
+}
+
+/// Parses the pencil marks of a 9x9 grid: 81 groups of digits listing the candidates of each cell, row by row, like `123 5 79 ...`.
+/// The groups are separated by whitespace, commas or `|`, and separators made of `-` and `+` only, like the borders of a boxed grid, are skipped.
+/// Returns the candidates in the convention of `SudokuGrid::from_candidates()`, or `None` if there aren't exactly 81 groups of digits from 1 to 9.
+pub fn parse_candidates(data: &str) -> Option<[[u16; 9]; 9]> {
+    let masks = data
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '|')
+        .filter(|token| !token.is_empty() && !token.chars().all(|c| c == '-' || c == '+'))
+        .map(|token| token.chars().try_fold(0u16, |mask, c| c.to_digit(10).filter(|&digit| digit > 0).map(|digit| mask | 1 << (digit - 1))))
+        .collect::<Option<Vec<u16>>>()?;
+
+    (masks.len() == 81).then(|| std::array::from_fn(|y| std::array::from_fn(|x| masks[y * 9 + x])))
+}
+
+/// Parses 9x9 grid data holding a character per cell: a digit, or any of `.`, `0`, `_`, `*` and `x` for an empty cell.
+/// Whitespace and commas are ignored, so that the cells can be separated or laid out on several lines in any way.
+pub fn parse_grid(data: &str) -> Result<SudokuGrid, ParseError> {
+    let mut values = Vec::new();
+
+    for (position, c) in data.chars().enumerate().map(|(i, c)| (i + 1, c)) {
+        match c {
+            c if c.is_whitespace() || c == ',' => {},
+            '.' | '0' | '_' | '*' | 'x' | 'X' => values.push(0),
+            c => values.push(c.to_digit(10).ok_or(ParseError::InvalidChar { c, position })? as u8)
+        }
+    }
+
+    SudokuGrid::from_data(&values).map_err(|_| ParseError::CellCount { found: values.len(), expected: 81 })
+}
+
+/// Parses a grid in the standard 81-character line format: a digit per cell, with `0` or `.` for an empty cell.
+/// Returns `None` if the line doesn't have exactly 81 characters or holds any other character.
+pub fn parse_line_grid(line: &str) -> Option<SudokuGrid> {
+    let values = line
+        .chars()
+        .map(|c| match c {
+            '.' => Some(0),
+            c => c.to_digit(10).map(|digit| digit as u8)
+        })
+        .collect::<Option<Vec<u8>>>()?;
+
+    SudokuGrid::from_data(&values).ok()
+}
+
+/// Parses a grid in the SDK (SadMan) format: 9 lines of 9 characters, with a digit per cell and `.` or `0` for an empty cell.
+/// Blank lines and comment lines starting with `#` are skipped, as well as spaces within the lines.
+/// Returns `None` if there aren't exactly 9 such lines of 9 cells.
+pub fn parse_sdk_grid(data: &str) -> Option<SudokuGrid> {
+    let lines: Vec<String> = data
+        .lines()
+        .map(|line| line.replace(' ', ""))
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+
+    if lines.len() != 9 || lines.iter().any(|line| line.chars().count() != 9) {
+        return None
+    }
+    parse_line_grid(&lines.concat())
+}
+
+/// Parses a grid in the boxed format of the `Display` implementation, where `_` marks an empty cell.
+/// Returns `None` if the data doesn't look like a boxed grid or doesn't hold exactly 81 cells.
+pub fn parse_boxed_grid(data: &str) -> Option<SudokuGrid> {
+    if !data.contains('|') {
+        return None
+    }
+
+    let values = data
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("|-"))// The borders hold no cells
+        .flat_map(|line| line.chars())
+        .filter_map(|c| match c {
+            '_' => Some(0),
+            c => c.to_digit(10).map(|digit| digit as u8)
+        })
+        .collect::<Vec<u8>>();
+
+    SudokuGrid::from_data(&values).ok()
+}
+
+/// Parses direct grid data in the format of `parse_grid()`, discarding the reason of a failure.
+pub fn parse_grid_data(data: &str) -> Option<SudokuGrid> {
+    parse_grid(data).ok()
+}
+
+/// Reads the content of a file at the path referred by a str.
+fn read_data_from_file(path: &str) -> Option<String> {
+    read_puzzle_file(path)
+        .ok()// We don't care about the error
+        .map(|s| s.trim().replace(' ', "")) // Trims the content string and gets rid of useless whitespaces.
+}
+
+/// Opens a puzzle file for buffered reading.
+/// Files ending in `.gz` are decompressed on the fly, any other file is read as is.
+pub fn open_puzzle_file(path: &str) -> std::io::Result<Box<dyn BufRead>> {
+    let file = File::open(path)?;
+    let gzipped = std::path::Path::new(path).extension().is_some_and(|extension| extension.eq_ignore_ascii_case("gz"));
+    Ok(match gzipped {
+        true => Box::new(BufReader::new(flate2::read::GzDecoder::new(file))),
+        false => Box::new(BufReader::new(file))
+    })
+}
+
+/// Reads the whole content of a puzzle file, decompressing it first if it ends in `.gz`.
+pub fn read_puzzle_file(path: &str) -> std::io::Result<String> {
+    let mut content = String::new();
+    open_puzzle_file(path)?.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+/// Solves, rates and checks the uniqueness of a puzzle, then combines the results into a single JSON object.
+/// Both the solving and the counting of the solutions are bounded by the iteration cap and the timeout of `options`, while the rating only propagates deductions, without any search.
+/// If the puzzle can't be solved or its solutions can't be counted, the object holds an error message instead of the other results.
+pub fn json_full_report(grid: SudokuGrid, options: &SolveOptions) -> serde_json::Value {
+    let puzzle = grid.rows();
+    let result = solve_with_options(grid.clone(), options).and_then(|solved_grid| Ok((solved_grid, count_solutions_with_options(&grid, 2, options)?)));
+
+    match result {
+        Ok((solved_grid, count)) => json!({
+            "puzzle": puzzle,
+            "solution": solved_grid.rows(),
+            "unique": count == 1,
+            "difficulty": grade(&grid).to_string()
+        }),
+        Err(err) => json!({
+            "puzzle": puzzle,
+            "error": err.to_string()
+        })
+    }
+}
+
+/// Lays out a printable worksheet: the puzzle, then its solution labeled as the answer key, both in the boxed format.
+pub fn worksheet(puzzle: &SudokuGrid, solution: &SudokuGrid) -> String {
+    format!("Puzzle{}\nAnswer Key{}", puzzle, solution)
+}
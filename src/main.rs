@@ -1,482 +1,789 @@
-use std::fmt::{Display, Formatter};
-use std::fs::File;
-use std::io::Read;
-
-// Three libraries are used:
-// - clap for program argument parsing
-// - rand for random number generation
-// - regex for regex matching in input strings
-use clap::{arg, Arg, Command, value_parser};
-use rand::{Rng, thread_rng};
-use regex::Regex;
-
-#[cfg(test)]
-mod tests;
-
-/// Structure that represents a Sudoku grid (9*9)
-struct SudokuGrid {
-    /// size must be 81
-    data: Vec<u8>
+use std::any::Any;
+use std::cell::Cell;
+use std::io::{BufRead, IsTerminal, Read};
+use std::path::Path;
+use std::time::Duration;
+
+// On top of the solver library, clap is used for program argument parsing
+use clap::{arg, Arg, ArgAction, ArgMatches, Command, value_parser};
+use rand::{SeedableRng, rngs::StdRng};
+mod interactive;
+
+use sudoku_solver::recognition::default_recognizer;
+use sudoku_solver::{BatchReport, BUILTIN_PUZZLES, Constraint, Difficulty, GENERATION_ATTEMPTS_DEFAULT, GridKind, MAX_ITERATIONS_DEFAULT, Sandwich, SamuraiGrid, SolveOptions, SolveStats, SudokuGrid, SudokuSolvingError, Symmetry, cell_ref, explain, grade, json_full_report, open_puzzle_file, read_puzzle_file, parse_cages, parse_grid, parse_grid_info, parse_regions, parse_sized_grid_info, solve_dlx, solve_batch_with, solve_samurai, solve_with_constraints, solve_with_stats, worksheet};
+
+/// Arguments of the program obtained from `parse_arguments()`.
+struct ProgramArguments {
+    task: Task,
+    max_iterations: u32,
+    /// Time the backtracking solver can take before giving up, on top of the iteration cap.
+    timeout: Option<Duration>,
+    /// Whether the combined JSON report should be printed instead of the solved grid.
+    json_full: bool,
+    /// Whether a printable worksheet with the puzzle and its answer key should be printed instead of the solved grid.
+    worksheet: bool,
+    /// Format the solved grid is printed in.
+    output: OutputFormat,
+    /// Separator of the values in the flat output format.
+    delimiter: char,
+    /// File the resulting grid is written to instead of being printed.
+    out_file: Option<String>,
+    /// Additional rules of the variant the grid is solved as, empty for classic Sudoku.
+    constraints: Vec<Box<dyn Constraint>>,
+    /// Algorithm used to solve the grid.
+    solver: Solver,
+    /// Whether the clues and the solved-in digits of the solved grid are shown in different colors.
+    color: bool,
+    /// Whether grids are drawn with Unicode box-drawing characters rather than ASCII.
+    unicode: bool,
+    /// Whether the amount of candidates of each empty cell of the grid is printed instead of its solution, or instead of the generated puzzle.
+    candidates: bool,
+    /// Seed of the random generation, which makes it reproducible.
+    seed: Option<u64>,
+    /// Whether the progress messages and headings printed on stderr are left out.
+    quiet: bool,
+    /// Whether the statistics of the solving process are printed on stderr.
+    stats: bool
 }
 
-impl SudokuGrid {
-    fn set(&mut self, x:usize, y:usize, value: u8) {
-        self.data[y * 9 + x] = value
-    }
-
-    fn get(&self, x: usize, y: usize) -> u8 {
-        match self.data.get(y * 9 + x) {
-            Some(&num) => num,
-            None => 0
-        }
-    }
-
-    /// Returns a vec of all the values in the specified row of the grid.
-    fn row(&self, y: usize) -> Vec<u8> {
-        let mut row_contents = Vec::with_capacity(9);
-
-        for x in 0..9 {
-            let value = self.get(x, y);
-            row_contents.push(value);
-        }
-
-        row_contents
+/// Enum of the tasks the program can carry out.
+enum Task {
+    /// Solves a single grid.
+    Solve(SudokuGrid),
+    /// Checks that a single grid follows the rules, reporting the outcome through the exit status only.
+    Validate(SudokuGrid),
+    /// Rates the difficulty of a single grid without printing its solution.
+    Grade(SudokuGrid),
+    /// Solves a single grid step by step, printing every deduction.
+    Explain(SudokuGrid),
+    /// Lets the user fill in a single grid in the terminal.
+    Interactive(SudokuGrid),
+    /// Solves a Samurai Sudoku, which is boxed since it holds five grids.
+    Samurai(Box<SamuraiGrid>),
+    /// Generates a puzzle of the specified difficulty, whose clues follow the specified symmetry.
+    Generate(Difficulty, Symmetry),
+    /// Checks that a proposed solution solves a puzzle.
+    Verify {
+        puzzle: SudokuGrid,
+        solution: SudokuGrid
+    },
+    /// Solves every puzzle of a file, one per line.
+    Batch {
+        path: String,
+        /// Whether the batch stops at the first puzzle that can't be solved.
+        fail_fast: bool
     }
+}
 
-    /// Returns a vec of all the values in the specified column of the grid.
-    fn column(&self, x: usize) -> Vec<u8> {
-        let mut column_contents = Vec::with_capacity(9);
+/// Enum of the algorithms that can solve a single grid.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Solver {
+    /// The iterative backtracking of `solve()`, bounded by the maximum amount of iterations.
+    Backtrack,
+    /// Algorithm X with dancing links, see `solve_dlx()`.
+    Dlx
+}
 
-        for y in 0..9 {
-            let value = self.get(x, y);
-            column_contents.push(value)
-        }
+/// Enum of the formats the solved grid can be printed in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum OutputFormat {
+    /// The boxed grid of the `Display` implementation.
+    Grid,
+    /// The 81 values on a single line, separated by the chosen delimiter (CSV-like).
+    Flat,
+    /// The standard 81-character format with a digit per cell.
+    Line,
+    /// The SDK format, with a line of 9 characters per row.
+    Sdk,
+    /// A Rust `vec!` literal, ready to be pasted into code such as tests.
+    Rust,
+    /// An SVG image of the grid.
+    Svg,
+    /// A CSV row per grid row, with blank empty cells.
+    Csv
+}
 
-        column_contents
+/// Help of --grid-image, which depends on the recognizer the program is built with.
+#[cfg(feature = "ocr")]
+const GRID_IMAGE_HELP: &str = "Image showing the sudoku grid to solve, read with the tesseract program (which must be installed). The image should show every cell, with '0' or '.' in the empty ones.";
+/// Help of --grid-image, which depends on the recognizer the program is built with.
+#[cfg(not(feature = "ocr"))]
+const GRID_IMAGE_HELP: &str = "Image showing the sudoku grid to solve. Without the 'ocr' feature, the grid is read from a transcript next to the image: the text file of the same name with a '.txt' extension, in any format accepted by --grid.";
+
+/// Arguments of the program when no subcommand is used, see `argument()`.
+const PROGRAM_ARGUMENTS: &[&str] = &["templates", "grid", "grid_env", "grid_image", "max_solving_iterations", "timeout", "stats", "json_full", "output", "delimiter", "out_file", "rust", "batch", "batch_fail_fast", "worksheet", "validate_only", "sandwich", "solver", "generate", "verify", "solution", "symmetry", "seed", "size", "variant", "cages", "regions", "grade", "explain", "interactive", "display", "color", "quiet"];
+/// Arguments of the `solve` subcommand.
+const SOLVE_ARGUMENTS: &[&str] = &["grid", "grid_env", "grid_image", "batch", "batch_fail_fast", "size", "variant", "cages", "regions", "sandwich", "seed", "max_solving_iterations", "timeout", "stats", "solver", "json_full", "worksheet", "explain", "interactive", "output", "delimiter", "out_file", "rust", "display", "color", "quiet"];
+/// Arguments of the `generate` subcommand, where the difficulty is given with --difficulty.
+const GENERATE_ARGUMENTS: &[&str] = &["generate", "symmetry", "seed", "output", "delimiter", "out_file", "display", "quiet"];
+/// Arguments of the `grade` subcommand.
+const GRADE_ARGUMENTS: &[&str] = &["grid", "grid_env", "grid_image", "variant", "cages", "regions", "seed", "quiet"];
+/// Arguments of the `validate` subcommand.
+const VALIDATE_ARGUMENTS: &[&str] = &["grid", "grid_env", "grid_image", "size", "variant", "cages", "regions", "sandwich", "seed", "quiet"];
+
+/// Returns the argument of the specified id, as it is shared by the flat argument set of the program and its subcommands.
+/// `ids` are the ids of the arguments of the command it belongs to: its conflicts and requirements only involve these, since the subcommands only have some of the arguments.
+fn argument(id: &'static str, ids: &[&'static str]) -> Arg {
+    let keep = |others: &[&'static str]| others.iter().copied().filter(|other| ids.contains(other)).collect::<Vec<&'static str>>();
+    match id {
+        "templates" => arg!(--templates "Lists all the available sudoku grid templates.")
+            .required(false),
+        "grid" => Arg::new("grid")
+            .short('g')
+            .long("grid")
+            .value_name("TEMPLATE | DATA | FILE")
+            .help("Name of template, direct or file data (numbers separated by commas) of the sudoku grid to solve, such as a '.csv' file of 9 rows. Files ending in '.gz' are decompressed first. '-' reads the grid from the standard input.")
+            .required_unless_present_any(keep(&["templates", "grid_env", "grid_image", "batch", "generate", "cages", "verify"])),
+        "grid_env" => Arg::new("grid_env")
+            .long("grid-env")
+            .value_name("VARNAME")
+            .help("Name of an environment variable holding the sudoku grid to solve, in any format accepted by --grid.")
+            .conflicts_with_all(keep(&["grid"])),
+        "grid_image" => Arg::new("grid_image")
+            .long("grid-image")
+            .value_name("FILE")
+            .help(GRID_IMAGE_HELP)
+            .conflicts_with_all(keep(&["grid", "grid_env"])),
+        "max_solving_iterations" => arg!(--max_solving_iterations <MAX_ITERATIONS> "Maximum number of iterations before the solving process gives up (default is 1000000). 0 lifts the cap, so that the solving runs to completion, which may take very long on a crafted grid.")
+            .required(false)
+            .alias("max-solving-iterations")
+            .value_parser(value_parser!(u32)),
+        "timeout" => Arg::new("timeout")
+            .long("timeout")
+            .value_name("SECONDS")
+            .help("Time the solving process can take before giving up, in seconds. The maximum number of iterations still applies. Not available with the dlx solver.")
+            .value_parser(value_parser!(u64).range(1..))
+            .conflicts_with_all(keep(&["sandwich"])),
+        "stats" => Arg::new("stats")
+            .long("stats")
+            .help("Prints the statistics of the solving process on stderr: the iterations, the dead ends stepped back from (backtracks), the deepest pending guess and the elapsed time.")
+            .conflicts_with_all(keep(&["batch", "generate", "json_full", "sandwich", "solver"]))
+            .action(ArgAction::SetTrue),
+        "json_full" => Arg::new("json_full")
+            .long("json-full")
+            .help("Prints the puzzle, its solution, its uniqueness and its difficulty as a single JSON object.")
+            .action(ArgAction::SetTrue),
+        "output" => Arg::new("output")
+            .long("output")
+            .value_name("FORMAT")
+            .help("Format of the solved grid: 'grid' for the boxed grid (default), 'flat' for the 81 values on a single line, 'line' for the standard 81-character format, 'sdk' for 9 lines of 9 characters, 'svg' for an SVG image or 'csv' for 9 rows of comma-separated cells.")
+            .value_parser(["grid", "flat", "line", "sdk", "svg", "csv"]),
+        "delimiter" => Arg::new("delimiter")
+            .long("delimiter")
+            .value_name("DELIMITER")
+            .help("Separator of the values in the flat output format: 'comma' (default), 'semicolon' or 'tab'.")
+            .value_parser(["comma", "semicolon", "tab"]),
+        "out_file" => Arg::new("out_file")
+            .long("out-file")
+            .value_name("FILE")
+            .help("Writes the solved or generated grid to a file, in the format chosen with --output, instead of printing it.")
+            .conflicts_with_all(keep(&["batch", "json_full", "worksheet"])),
+        "rust" => Arg::new("rust")
+            .long("rust")
+            .help("Prints the solved grid as a Rust vec! literal, in the layout of the grids of the tests.")
+            .conflicts_with_all(keep(&["output"]))
+            .action(ArgAction::SetTrue),
+        "batch" => Arg::new("batch")
+            .long("batch")
+            .value_name("FILE")
+            .help("File holding one puzzle per line, either as an 81-char line or comma separated values. Every puzzle is solved and a line with its solution (or an error marker) is printed. Files ending in '.gz' are decompressed on the fly.")
+            .conflicts_with_all(keep(&["grid", "grid_env", "grid_image"])),
+        "batch_fail_fast" => Arg::new("batch_fail_fast")
+            .long("batch-fail-fast")
+            .help("Stops the batch at the first puzzle that can't be parsed or solved, reporting its line number.")
+            .requires("batch")
+            .action(ArgAction::SetTrue),
+        "worksheet" => Arg::new("worksheet")
+            .long("worksheet")
+            .help("Prints a worksheet made of the puzzle followed by its solution as an answer key.")
+            .conflicts_with_all(keep(&["json_full"]))
+            .action(ArgAction::SetTrue),
+        "validate_only" => Arg::new("validate_only")
+            .long("validate-only")
+            .help("Only checks that the grid follows the rules, without solving it. Prints nothing and exits with status 0 if the grid is valid, 2 otherwise.")
+            .conflicts_with_all(keep(&["batch", "json_full", "worksheet"]))
+            .action(ArgAction::SetTrue),
+        "sandwich" => Arg::new("sandwich")
+            .long("sandwich")
+            .value_name("FILE")
+            .help("File holding the sandwich sums of the grid: the 9 row sums then the 9 column sums, with '-' for a row or column without a clue.")
+            .conflicts_with_all(keep(&["batch", "json_full"])),
+        "solver" => Arg::new("solver")
+            .long("solver")
+            .value_name("SOLVER")
+            .help("Algorithm solving the grid: 'backtrack' (default) or 'dlx' for Algorithm X with dancing links, which needs no iteration cap.")
+            .value_parser(["backtrack", "dlx"])
+            .conflicts_with_all(keep(&["sandwich"])),
+        "generate" => Arg::new("generate")
+            .long("generate")
+            .value_name("DIFFICULTY")
+            .help("Generates and prints a uniquely solvable puzzle of the specified difficulty: 'easy', 'medium', 'hard' or 'expert'.")
+            .value_parser(["easy", "medium", "hard", "expert"])
+            .conflicts_with_all(keep(&["grid", "grid_env", "grid_image", "batch", "json_full", "worksheet", "validate_only", "sandwich"])),
+        "verify" => Arg::new("verify")
+            .long("verify")
+            .value_name("PUZZLE")
+            .help("Checks that the grid given with --solution is a valid solution of this puzzle, in any format accepted by --grid, instead of solving anything. Prints 'OK', or the first problem and exits with status 1.")
+            .requires("solution")
+            .conflicts_with_all(keep(&["grid", "grid_env", "grid_image", "batch", "generate", "json_full", "worksheet", "validate_only", "sandwich", "cages"])),
+        "solution" => Arg::new("solution")
+            .long("solution")
+            .value_name("GRID")
+            .help("Proposed solution checked against the puzzle of --verify.")
+            .requires("verify"),
+        "symmetry" => Arg::new("symmetry")
+            .long("symmetry")
+            .value_name("SYMMETRY")
+            .help("Symmetry the clues of the generated puzzle follow: 'none' (default), 'rotational' for a half turn, 'horizontal' or 'vertical' for a mirror across the middle row or column, or 'diagonal' for a mirror across the main diagonal.")
+            .value_parser(["none", "rotational", "horizontal", "vertical", "diagonal"])
+            .requires("generate"),
+        "seed" => Arg::new("seed")
+            .long("seed")
+            .value_name("SEED")
+            .help("Seed of the random generation of --generate and of the 'random' template, so that the same seed always gives the same grid.")
+            .value_parser(value_parser!(u64)),
+        "size" => Arg::new("size")
+            .long("size")
+            .value_name("SIZE")
+            .help("Side length of the grid: 9 (default) or 16 for a hexadoku, whose digits are written 0 to F with '.' for empty cells.")
+            .value_parser(["9", "16"])
+            .conflicts_with_all(keep(&["batch", "generate", "json_full", "rust", "sandwich", "solver"])),
+        "variant" => Arg::new("variant")
+            .long("variant")
+            .value_name("VARIANT")
+            .help("Rules of the grid: 'classic' (default), 'x' for an X-Sudoku, where both main diagonals must also hold every digit once, 'antiknight' for an Anti-Knight Sudoku, where cells a chess knight's move apart can't hold the same digit, 'killer' for a Killer Sudoku, whose cages are given with --cages, 'jigsaw' for a Jigsaw Sudoku, whose irregular regions are given with --regions, or 'samurai' for a Samurai Sudoku, whose five overlapping grids are given in a file of 21 lines laid out like the puzzle.")
+            .value_parser(["classic", "x", "antiknight", "killer", "jigsaw", "samurai"])
+            .conflicts_with_all(keep(&["batch", "generate", "json_full", "solver"])),
+        "cages" => Arg::new("cages")
+            .long("cages")
+            .value_name("FILE")
+            .help("File holding the cages of a Killer Sudoku, one per line: the sum of the cage followed by its cells, like '10 R1C1 R1C2'. Without --grid, the grid starts empty.")
+            .required_if_eq("variant", "killer")
+            .conflicts_with_all(keep(&["batch", "generate", "json_full", "size", "solver"])),
+        "regions" => Arg::new("regions")
+            .long("regions")
+            .value_name("FILE")
+            .help("File holding the regions of a Jigsaw Sudoku, which replace the 3x3 groups: 9 lines of 9 characters, where the cells of a region share the same character, like '112223333'.")
+            .required_if_eq("variant", "jigsaw")
+            .conflicts_with_all(keep(&["batch", "generate", "json_full", "size", "solver"])),
+        "grade" => Arg::new("grade")
+            .long("grade")
+            .help("Prints the difficulty of the grid according to the hardest technique needed to solve it: 'easy' (singles), 'medium' (pointing pairs, box-line reductions and naked pairs), 'hard' (naked triples) or 'expert' (guessing).")
+            .conflicts_with_all(keep(&["batch", "generate", "json_full", "worksheet", "validate_only", "sandwich", "size"]))
+            .action(ArgAction::SetTrue),
+        "explain" => Arg::new("explain")
+            .long("explain")
+            .help("Solves the grid step by step like a human would, printing every placed digit and removed candidate with the technique behind it, then the solved grid.")
+            .conflicts_with_all(keep(&["batch", "generate", "json_full", "worksheet", "validate_only", "sandwich", "size", "solver", "grade"]))
+            .action(ArgAction::SetTrue),
+        "interactive" => Arg::new("interactive")
+            .long("interactive")
+            .help("Opens the grid in the terminal to solve it by hand: the arrows move, digits are typed in, 'c' shows the candidates, 'h' gives a hint, 's' solves the rest, 'u' and 'r' undo and redo, and 'q' quits and prints the grid as it was left.")
+            .conflicts_with_all(keep(&["batch", "generate", "json_full", "worksheet", "validate_only", "sandwich", "size", "solver", "grade", "explain", "verify"]))
+            .action(ArgAction::SetTrue),
+        "display" => Arg::new("display")
+            .long("display")
+            .value_name("STYLE")
+            .help("Characters the grids are drawn with: 'ascii' (default) or 'unicode' for box-drawing characters, with heavier lines around the groups. 'candidates' prints the amount of candidates of each empty cell instead of solving the grid, with '*' for a naked single (a cell with a single candidate), '!' for a cell without candidates and '#' for a filled cell.")
+            .value_parser(["ascii", "unicode", "candidates"])
+            .conflicts_with_all(keep(&["color"])),
+        "color" => Arg::new("color")
+            .long("color")
+            .help("Shows the clues of the solved grid in bold and the digits filled by the solver in green. Ignored when the output isn't a terminal.")
+            .action(ArgAction::SetTrue),
+        "quiet" => Arg::new("quiet")
+            .short('q')
+            .long("quiet")
+            .help("Leaves out the progress messages and headings printed on stderr, such as the echo of the input grid and the batch summary. Errors are still reported.")
+            .action(ArgAction::SetTrue),
+        _ => unreachable!("unknown argument '{}'", id)
     }
+}
 
-    /// Returns a vec of all the values in the specified group (3*3 cell) of the grid.
-    fn group(&self, x: usize, y:usize) -> Vec<u8> {
-        let mut group_contents = Vec::with_capacity(9);
+/// Returns the arguments of a command from their ids, see `argument()`.
+fn arguments(ids: &[&'static str]) -> Vec<Arg> {
+    ids.iter().map(|id| argument(id, ids)).collect()
+}
 
-        let group_start_x = x - x % 3;
-        let group_start_y = y - y % 3;
+/// Parses the program arguments using clap into a Result that either holds our arguments or a String describing an error.
+/// TODO: Better error handling/description.
+fn parse_arguments() -> Result<ProgramArguments, String> {
+    let program_matches = Command::new("SudokuSolver")
+        .about("Solves Sudoku puzzles!")
+        .after_help("Exit status: 0 when the task succeeded, 1 for invalid arguments or another failure, 2 for a grid that breaks the rules, 3 for a grid without a single solution, 4 when the solver gave up (iterations, timeout or guess depth) or the generator ran out of attempts.")
+        .args(arguments(PROGRAM_ARGUMENTS))
+        // The flat argument set is kept for compatibility, the subcommands group the arguments of each task
+        .args_conflicts_with_subcommands(true)
+        .subcommand_negates_reqs(true)
+        .subcommand(Command::new("solve").about("Solves a grid, or every grid of a batch file.").args(arguments(SOLVE_ARGUMENTS)))
+        .subcommand(Command::new("generate").about("Generates a uniquely solvable puzzle.").args(arguments(GENERATE_ARGUMENTS)).mut_arg("generate", |arg| arg.long("difficulty").required(true)))
+        .subcommand(Command::new("grade").about("Prints the difficulty of a grid without solving it.").args(arguments(GRADE_ARGUMENTS)))
+        .subcommand(Command::new("validate").about("Checks that a grid follows the rules, reporting the outcome through the exit status only.").args(arguments(VALIDATE_ARGUMENTS)))
+        .subcommand(Command::new("templates").about("Lists all the available sudoku grid templates."))
+        .try_get_matches()
+        .unwrap_or_else(|err| {
+            // Help and version requests aren't errors
+            let _ = err.print();
+            std::process::exit(if err.use_stderr() { EXIT_ARGUMENTS } else { EXIT_SUCCESS })
+        });
+    let (subcommand, matches) = match program_matches.subcommand() {
+        Some((name, matches)) => (Some(name), matches),
+        None => (None, &program_matches)
+    };
+    let grade = flag(matches, "grade") || subcommand == Some("grade");
+    let validate_only = flag(matches, "validate_only") || subcommand == Some("validate");
 
-        for y_offset in 0..3 {
-            for x_offset in 0..3 {
-                let value = self.get(group_start_x + x_offset, group_start_y + y_offset);
-                group_contents.push(value)
-            }
+    // Print the available templates
+    if flag(matches, "templates") || subcommand == Some("templates") {
+        println!("Here are the available templates:");
+        for (name, description) in BUILTIN_PUZZLES {
+            println!("'{}': {}", name, description);
         }
+        println!("'random': a randomly generated solved grid.");
 
-        group_contents
-    }
-
-    /// Checks whether the given value can be inserted in the given location (assuming there is no value already).
-    /// This check is done according to the sudoku rules:
-    /// - All digits on the row must be unique
-    /// - All digits on the column must be unique
-    /// - All digits in the 3x3 group must be unique
-    fn check(&self, x: usize, y: usize, value: u8) -> bool {
-        if self.row(y).contains(&value) {
-            false
-        } else if self.column(x).contains(&value) {
-            false
-        } else if self.group(x, y).contains(&value) {
-            false
-        } else {
-            true
-        }
+        return Err(String::new())
     }
 
-    /// Checks if the grid can be solved or not.
-    fn check_grid(&self) -> bool {
-        if self.is_empty() {
-            return false
-        }
-
-        for y in 0..8 {
-            for x in 0..8 {
-                let value = self.get(x, y);
-                if value != 0 {
-                    // We filter and count occurrences because in opposition to `check()` the value we check for is already present.
-                    if self.row(y).iter().filter(|&&v| v == value).count() > 1 {
-                        return false
-                    } else if self.column(x).iter().filter(|&&v| v == value).count() > 1 {
-                        return false
-                    } else if self.group(x, y).iter().filter(|&&v| v == value).count() > 1 {
-                        return false
-                    }
-                }
+    let task = match (value::<String>(matches, "batch"), value::<String>(matches, "generate").map(|s| s.as_str())) {
+        (None, None) if value::<String>(matches, "verify").is_some() => {
+            let parse = |id: &str| parse_grid_info(value::<String>(matches, id).map_or("", |s| s.as_str())).map_err(|err| format!("the {} couldn't be parsed ({}).", if id == "verify" { "puzzle" } else { "solution" }, err));
+            Task::Verify {
+                puzzle: parse("verify")?,
+                solution: parse("solution")?
+            }
+        },
+        (Some(path), _) => Task::Batch {
+            path: path.clone(),
+            fail_fast: flag(matches, "batch_fail_fast")
+        },
+        (None, Some(difficulty)) => Task::Generate(match difficulty {
+            "easy" => Difficulty::Easy,
+            "medium" => Difficulty::Medium,
+            "hard" => Difficulty::Hard,
+            _ => Difficulty::Expert
+        }, match value::<String>(matches, "symmetry").map(|s| s.as_str()) {
+            Some("rotational") => Symmetry::Rotational180,
+            Some("horizontal") => Symmetry::Horizontal,
+            Some("vertical") => Symmetry::Vertical,
+            Some("diagonal") => Symmetry::Diagonal,
+            _ => Symmetry::None
+        }),
+        (None, None) if value::<String>(matches, "variant").is_some_and(|variant| variant == "samurai") => {
+            if validate_only || grade || ["explain", "interactive", "worksheet"].iter().any(|&id| flag(matches, id)) || ["grid_image", "cages", "regions", "sandwich"].iter().any(|&id| value::<String>(matches, id).is_some()) {
+                return Err(String::from("a Samurai Sudoku can only be solved from a grid file, without cages, regions or sandwich sums."))
+            }
+            let grid_info = read_grid_info(matches)?;
+            let data = match std::path::Path::new(&grid_info).is_file() {
+                true => read_puzzle_file(&grid_info).map_err(|err| format!("couldn't read samurai file '{}': {}", grid_info, err))?,
+                false => grid_info
+            };
+            Task::Samurai(Box::new(SamuraiGrid::parse(&data).ok_or(String::from("samurai grid should hold 21 lines laid out like the puzzle: 18 cells on the lines of the top and bottom grids, 21 cells on the lines crossing the three middle grids and 9 cells on the lines of the center grid alone."))?))
+        },
+        (None, None) => {
+            let grid_info = read_grid_info(matches)?;
+            let size = value::<String>(matches, "size").map_or(9, |size| size.parse().unwrap_or(9));
+            let mut grid = match (value::<String>(matches, "grid_image"), value::<String>(matches, "cages")) {
+                (Some(path), _) => default_recognizer().recognize(Path::new(path)).map_err(|err| format!("the grid of the image couldn't be recognized ({}).", err))?,
+                // The cages hold the clues of a Killer Sudoku, which may have no given digit
+                (None, Some(_)) if grid_info.is_empty() => SudokuGrid::empty(),
+                _ if grid_info == "random" && value::<u64>(matches, "seed").is_some() => SudokuGrid::random_solution(&mut StdRng::seed_from_u64(value::<u64>(matches, "seed").copied().unwrap_or_default())),
+                _ => parse_sized_grid_info(&grid_info, size).map_err(|err| format!("grid info couldn't be parsed ({}). Try using a template or directly specifying the grid data (with numbers between commas, like so: '0,6,4,8,0,0,1,0,...').", err))?
+            };
+            match value::<String>(matches, "variant").map(|variant| variant.as_str()) {
+                Some("x") => grid.set_kind(GridKind::Diagonal),
+                Some("antiknight") => grid.set_kind(GridKind::AntiKnight),
+                _ => {}
+            }
+            if let Some(path) = value::<String>(matches, "regions") {
+                let data = std::fs::read_to_string(path).map_err(|err| format!("couldn't read regions file '{}': {}", path, err))?;
+                let regions = parse_regions(&data).ok_or(String::from("regions file should hold 9 lines of 9 characters, each character marking the 9 cells of a region."))?;
+                grid.set_regions(Some(regions))?;
+            }
+            if let Some(path) = value::<String>(matches, "cages") {
+                let data = std::fs::read_to_string(path).map_err(|err| format!("couldn't read cages file '{}': {}", path, err))?;
+                grid.set_cages(parse_cages(&data).ok_or(String::from("cages file should hold a cage per line: its sum (up to 45) followed by its cells (like 'R1C1'), each cell in a single cage."))?);
+            }
+            if validate_only {
+                Task::Validate(grid)
+            } else if grade {
+                Task::Grade(grid)
+            } else if flag(matches, "explain") {
+                Task::Explain(grid)
+            } else if flag(matches, "interactive") {
+                Task::Interactive(grid)
+            } else {
+                Task::Solve(grid)
             }
         }
+    };
 
-        true
+    // Algorithm X has no deadline, unlike the default backtracking solver
+    if value::<u64>(matches, "timeout").is_some() && value::<String>(matches, "solver").is_some_and(|solver| solver == "dlx") {
+        return Err(String::from("--timeout can't be used with the 'dlx' solver, which always runs to completion."))
     }
 
-    /// Returns true if there is no value set in the grid.
-    fn is_empty(&self) -> bool {
-        !self.data.iter().any(|&v| v > 0)
+    let mut constraints: Vec<Box<dyn Constraint>> = Vec::new();
+    if let Some(path) = value::<String>(matches, "sandwich") {
+        let data = std::fs::read_to_string(path).map_err(|err| format!("couldn't read sandwich file '{}': {}", path, err))?;
+        let sandwich = Sandwich::parse(&data).ok_or(String::from("sandwich file should hold 18 sums between 0 and 35 (or '-' for no clue), separated by commas or whitespace."))?;
+        constraints.push(Box::new(sandwich));
     }
 
-    /// Creates an empty grid
-    fn empty() -> SudokuGrid {
-        SudokuGrid {
-            data: vec![0; 81]
+    Ok(ProgramArguments {
+        task,
+        max_iterations: value::<u32>(matches, "max_solving_iterations").copied().unwrap_or(MAX_ITERATIONS_DEFAULT),
+        timeout: value::<u64>(matches, "timeout").map(|seconds| Duration::from_secs(*seconds)),
+        json_full: flag(matches, "json_full"),
+        worksheet: flag(matches, "worksheet"),
+        output: match value::<String>(matches, "output").map(|s| s.as_str()) {
+            _ if flag(matches, "rust") => OutputFormat::Rust,
+            Some("flat") => OutputFormat::Flat,
+            Some("line") => OutputFormat::Line,
+            Some("sdk") => OutputFormat::Sdk,
+            Some("svg") => OutputFormat::Svg,
+            Some("csv") => OutputFormat::Csv,
+            _ => OutputFormat::Grid
+        },
+        delimiter: match value::<String>(matches, "delimiter").map(|s| s.as_str()) {
+            Some("semicolon") => ';',
+            Some("tab") => '\t',
+            _ => ','
+        },
+        out_file: value::<String>(matches, "out_file").cloned(),
+        constraints,
+        solver: match value::<String>(matches, "solver").map(|s| s.as_str()) {
+            Some("dlx") => Solver::Dlx,
+            _ => Solver::Backtrack
+        },
+        // Escape codes would only clutter files and pipes
+        color: flag(matches, "color") && std::io::stdout().is_terminal(),
+        unicode: value::<String>(matches, "display").is_some_and(|style| style == "unicode"),
+        candidates: value::<String>(matches, "display").is_some_and(|style| style == "candidates"),
+        seed: value::<u64>(matches, "seed").copied(),
+        quiet: flag(matches, "quiet"),
+        stats: flag(matches, "stats")
+    })
+}
+
+/// Returns the grid given to the program: the value of --grid, the content of the standard input for '-', or the value of the environment variable named by --grid-env.
+fn read_grid_info(matches: &ArgMatches) -> Result<String, String> {
+    Ok(match value::<String>(matches, "grid_env") {
+        Some(name) => match std::env::var(name) {
+            Ok(value) if !value.trim().is_empty() => value,
+            Ok(_) => return Err(format!("environment variable '{}' is empty.", name)),
+            Err(_) => return Err(format!("environment variable '{}' is not set or isn't valid unicode.", name))
+        },
+        None => match value::<String>(matches, "grid").map(|s| s.as_str()) {
+            Some("-") => read_grid_from_stdin()?,
+            grid => grid.unwrap_or_default().to_string()
         }
-    }
+    })
+}
 
-    /// Creates a grid with random values.
-    /// The returned grid may not be a valid sudoku grid.
-    fn randomly_filled() -> SudokuGrid {
-        let mut data: Vec<u8> = vec![0; 81];
+/// Returns the value of an argument, or `None` if it wasn't given or isn't an argument of the command, since the subcommands only have some of the arguments.
+fn value<'a, T: Any + Clone + Send + Sync>(matches: &'a ArgMatches, id: &str) -> Option<&'a T> {
+    matches.try_get_one::<T>(id).ok().flatten()
+}
 
-        let mut rng = thread_rng();
+/// Checks if a flag was given, see `value()`.
+fn flag(matches: &ArgMatches, id: &str) -> bool {
+    value::<bool>(matches, id).copied().unwrap_or(false)
+}
 
-        for i in 0..(9*9) {
-            if rng.gen_range(0..5) == 0 {
-                data[i] = rng.gen_range(1..=9)
-            }
-        }
+/// Reads the whole standard input as grid data, trimmed and without spaces like the content of a grid file.
+fn read_grid_from_stdin() -> Result<String, String> {
+    let mut content = String::new();
+    std::io::stdin().read_to_string(&mut content).map_err(|err| format!("couldn't read the grid from stdin: {}", err))?;
 
-        SudokuGrid {
-            data
-        }
+    let content = content.trim().replace(' ', "");
+    if content.is_empty() {
+        return Err(String::from("no grid was given on stdin."))
     }
+    Ok(content)
+}
 
-    /// Creates a valid sudoku grid with random values.
-    /// The valid grid is obtained after multiple iterations of `randomly_filled()`, therefore this method might return an empty grid.
-    fn valid_random() -> SudokuGrid {
-        let mut i = 0;
-        while i < 10000 {
-            let random_grid = SudokuGrid::randomly_filled();
-            if random_grid.check_grid() {
-                return random_grid
-            }
-            i += 1
+/// Solves every puzzle of a batch file (one puzzle per non-empty line) through `solve_batch_with()` and prints a line per puzzle: its solution in the flat format, or an error marker.
+/// The file is read one line at a time, so that large files never need to be held in memory. Puzzles with several solutions are rejected since they aren't proper sudokus.
+/// With `fail_fast`, the batch stops at the first puzzle that can't be parsed or solved and an error describing the offending line is returned.
+fn run_batch(path: &str, fail_fast: bool, options: SolveOptions, output: OutputFormat, delimiter: char) -> Result<BatchReport, String> {
+    let reader = open_puzzle_file(path).map_err(|err| format!("couldn't read batch file '{}': {}", path, err))?;
+    // Number of the line being solved, and the error that ended the reading of the file early
+    let line_number = Cell::new(0);
+    let read_error = Cell::new(None);
+
+    let puzzles = reader.lines()
+        .enumerate()
+        .map_while(|(index, line)| {
+            line_number.set(index + 1);
+            line.map_err(|err| read_error.set(Some(format!("couldn't read line {} of batch file '{}': {}", index + 1, path, err)))).ok()
+        })
+        .filter(|line| !line.trim().is_empty())
+        // Puzzles are either 81-char lines or comma separated values
+        .map(|line| parse_grid(line.trim()));
+
+    let mut failure = None;
+    let report = solve_batch_with(puzzles, &options.require_unique(true), |result| {
+        let result = match result {
+            Ok(result) => result.map_err(|err| (err.marker(), err.to_string())),
+            Err(err) => Err(("UNPARSABLE", format!("The puzzle couldn't be parsed: {}.", err)))
+        };
+        match result {
+            // Solutions stay on a single line so that the output can be diffed against the batch
+            Ok(solved_grid) if output == OutputFormat::Line => println!("{}", solved_grid.to_line_string()),
+            Ok(solved_grid) => println!("{}", solved_grid.to_flat_string(delimiter)),
+            Err((_, message)) if fail_fast => {
+                failure = Some(format!("line {}: {}", line_number.get(), message));
+                return false
+            },
+            Err((marker, _)) => println!("{}", marker)
         }
+        true
+    });
 
-        SudokuGrid::empty()
+    match read_error.take().or(failure) {
+        Some(err) => Err(err),
+        None => Ok(report)
     }
+}
 
-    /// Creates a grid with values from an example sudoku.
-    fn example_grid() -> SudokuGrid {
-        SudokuGrid {
-            data: vec![
-                5, 3, 0,   0, 7, 0,   0, 0, 0,
-                6, 0, 0,   1, 9, 5,   0, 0, 0,
-                0, 9, 8,   0, 0, 0,   0, 6, 0,
-
-                8, 0, 0,   0, 6, 0,   0, 0, 3,
-                4, 0, 0,   8, 0, 3,   0, 0, 1,
-                7, 0, 0,   0, 2, 0,   0, 0, 6,
-
-                0, 6, 0,   0, 0, 0,   2, 8, 0,
-                0, 0, 0,   4, 1, 9,   0, 0, 5,
-                0, 0, 0,   0, 8, 0,   0, 7, 9
-            ]
-        }
+/// Draws a grid like its `Display` implementation, or with box-drawing characters if `unicode` is set.
+fn draw_grid(grid: &SudokuGrid, unicode: bool) -> String {
+    if unicode {
+        grid.to_unicode_string()
+    } else {
+        grid.to_string()
     }
+}
 
-    /// Creates a grid holding the specified data.
-    fn from_data(data: &[u8]) -> SudokuGrid {
-        SudokuGrid {
-            data: Vec::from(data)
-        }
+/// Prints a progress message or a heading on stderr, unless `quiet` is set, so that stdout only holds the results and can be piped.
+fn diagnostic(quiet: bool, message: &str) {
+    if !quiet {
+        eprintln!("{}", message)
     }
 }
 
-// Display implementation for SudokuGrid: helps with displaying the grid in the console.
-impl Display for SudokuGrid {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut s = String::from("\n");
-        s.push_str("|-----------------|\n");
-
-        for row_index in 0..9 {
-            s.push_str("| ");
-
-            for cell_index in 0..9 {
-                let num = self.data.get(row_index * 9 + cell_index).filter(|&&v| v != 0).map(|v| v.to_string()).unwrap_or("_".to_string());
-
-                if cell_index != 0 && cell_index % 3 == 0 {
-                    s.push_str(" | ")
-                }
-
-                s.push_str(&num);
-            }
-            s.push_str(" |");
-            s.push('\n');
-
-            if (row_index + 1) % 3 == 0 {
-                s.push_str("|-----------------|\n")
-            }
-        }
+/// Prints a grid drawn by `draw_grid()` or `to_colored_string()` on stdout, without the blank lines around it.
+fn print_drawn_grid(drawn: &str) {
+    println!("{}", drawn.trim_matches('\n'))
+}
 
-        f.write_str(&s)
+/// Formats a grid in the specified output format.
+fn format_grid(grid: &SudokuGrid, output: OutputFormat, delimiter: char) -> String {
+    match output {
+        OutputFormat::Grid => grid.to_string(),
+        OutputFormat::Flat => grid.to_flat_string(delimiter),
+        OutputFormat::Line => grid.to_line_string(),
+        OutputFormat::Sdk => grid.to_sdk_string(),
+        OutputFormat::Rust => grid.to_rust_literal(),
+        OutputFormat::Svg => grid.to_svg(),
+        OutputFormat::Csv => grid.to_csv()
     }
 }
 
-// Clone implementation for SudokuGrid: helps with making a copy of an existing grid.
-impl Clone for SudokuGrid {
-    fn clone(&self) -> Self {
-        SudokuGrid {
-            data: self.data.clone()
-        }
+/// Writes a grid to a file in the specified output format, exiting the program if the file can't be written.
+fn write_grid(path: &str, grid: &SudokuGrid, output: OutputFormat, delimiter: char) {
+    if let Err(err) = std::fs::write(path, format_grid(grid, output, delimiter)) {
+        eprintln!("Couldn't write the grid to '{}': {}", path, err);
+        std::process::exit(EXIT_ARGUMENTS)
     }
 }
 
-/// Enum of the error kinds that the process of solving can encounter.
-enum SudokuSolvingError {
-    InvalidGrid,
-    Unsolvable,
-    IterationCountOverflow
+/// Builds the solving options of the iteration cap and the optional timeout.
+fn solve_options(max_iterations: u32, timeout: Option<Duration>) -> SolveOptions {
+    let options = SolveOptions::new(max_iterations);
+    match timeout {
+        Some(timeout) => options.timeout(timeout),
+        None => options
+    }
 }
 
-// Display implementation for SudokuSolvingError: helps with displaying the error after it has been caught.
-impl Display for SudokuSolvingError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            SudokuSolvingError::InvalidGrid => f.write_str("The supplied sudoku grid is invalid!"),
-            SudokuSolvingError::Unsolvable => f.write_str("The supplied sudoku is unsolvable!"),
-            SudokuSolvingError::IterationCountOverflow => f.write_str("The solving process was abnormally long and therefore interrupted.")
-        }
+/// Exit status of a task that succeeded, such as a solved grid.
+const EXIT_SUCCESS: i32 = 0;
+/// Exit status of arguments that couldn't be parsed, or of a task other than solving that failed (like a batch that couldn't be read or a mismatching solution).
+const EXIT_ARGUMENTS: i32 = 1;
+/// Exit status of a grid that breaks the rules (`SudokuSolvingError::InvalidGrid`).
+const EXIT_INVALID_GRID: i32 = 2;
+/// Exit status of a grid without a single solution (`SudokuSolvingError::Unsolvable` or `MultipleSolutions`).
+const EXIT_UNSOLVABLE: i32 = 3;
+/// Exit status of a solving that gave up because of a limit (its iterations, its timeout or its guess depth), or of a generation that ran out of attempts.
+const EXIT_GAVE_UP: i32 = 4;
+
+/// Returns the exit status of the program when solving failed with the specified error, so that scripts can branch on the outcome without parsing the output.
+fn exit_code(err: &SudokuSolvingError) -> i32 {
+    match err {
+        SudokuSolvingError::InvalidGrid(_) => EXIT_INVALID_GRID,
+        SudokuSolvingError::Unsolvable | SudokuSolvingError::MultipleSolutions => EXIT_UNSOLVABLE,
+        SudokuSolvingError::IterationCountOverflow(_) | SudokuSolvingError::Timeout | SudokuSolvingError::GuessDepthExceeded => EXIT_GAVE_UP
     }
 }
 
-/// Function that solves a sudoku grid.
-/// It takes two parameters: the grid to solve and the maximum amount of iterations it can take to solve
-fn solve(grid: SudokuGrid, max_iterations: u32) -> Result<SudokuGrid, SudokuSolvingError> {
-    if !grid.check_grid() {
-        return Err(SudokuSolvingError::InvalidGrid)
+/// Prints why the grid couldn't be solved on stderr, along with the partially filled grid when the solver ran out of iterations, then exits with the status of the error.
+fn exit_with_failure(err: &SudokuSolvingError) -> ! {
+    eprintln!("Failed to solve the sudoku: {}", err);
+    if let SudokuSolvingError::IterationCountOverflow(partial) = err {
+        eprintln!("Here is how far the solver got: {}", partial)
     }
+    std::process::exit(exit_code(err))
+}
 
-    let mut solved_grid = grid.clone();
-
-    // Keep track of the number of iterations
-    let mut iteration_count: u32 = 0;
-    let mut x: usize = 0;
-    let mut y: usize = 0;
-    // If we're iterating backward, it means we encountered a dead end with the current combination. We therefore go back to change it and try with another combination.
-    let mut iterating_forward = true;
-
-    while iteration_count < max_iterations {
-        // Check that we're not trying to replace a preset digit
-        if grid.get(x, y) == 0 {
-            if iterating_forward {
-                // Whether a digit can satisfy the cell at the current pos or not
-                let mut invalid = true;
-                for value in 1..=9 {
-                    if solved_grid.check(x, y, value) {
-                        invalid = false;
-                        solved_grid.set(x, y, value);
-                        break
-                    }
+fn main() {
+    match parse_arguments() {
+        Ok(ProgramArguments { task: Task::Batch { path, fail_fast }, max_iterations, timeout, output, delimiter, quiet, .. }) => {
+            match run_batch(&path, fail_fast, solve_options(max_iterations, timeout), output, delimiter) {
+                Ok(report) => diagnostic(quiet, &report.to_string()),
+                Err(err) => {
+                    eprintln!("Batch failed: {}", err);
+                    std::process::exit(EXIT_ARGUMENTS)
                 }
-
-                if invalid {
-                    // no digit could satisfy the cell we are trying to fill, so we need to go back and change the previous cells.
-                    iterating_forward = false;
-                    // Common block to go back. If we try going back while x = 0 and y = 0, the sudoku must be unsolvable
-                    if x == 0 {
-                        if y > 0 {
-                            x = 8;
-                            y -= 1;
-                        } else {
-                            return Err(SudokuSolvingError::Unsolvable)
-                        }
-                    } else {
-                        x -= 1
-                    }
-                } else {
-                    // Common block to go forward: we break off the loop when we complete the last index.
-                    if x >= 8 {
-                        if y >= 8 {
-                            break;
-                        } else {
-                            x = 0;
-                            y += 1;
-                        }
-                    } else {
-                        x += 1;
+            }
+        },
+        Ok(ProgramArguments { task: Task::Generate(difficulty, symmetry), output, delimiter, out_file, unicode, candidates, seed, quiet, .. }) => {
+            let mut rng = match seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy()
+            };
+            let puzzle = SudokuGrid::try_generate_symmetric(difficulty, symmetry, &mut rng, GENERATION_ATTEMPTS_DEFAULT).unwrap_or_else(|err| {
+                eprintln!("Failed to generate the puzzle: {}", err);
+                std::process::exit(EXIT_GAVE_UP)
+            });
+            match (out_file, output) {
+                (Some(path), _) => write_grid(&path, &puzzle, output, delimiter),
+                (None, OutputFormat::Grid) => {
+                    diagnostic(quiet, &format!("Generated puzzle ({}):", difficulty));
+                    print_drawn_grid(&if candidates { puzzle.to_candidate_counts_string() } else { draw_grid(&puzzle, unicode) })
+                },
+                (None, _) => println!("{}", format_grid(&puzzle, output, delimiter).trim_end())
+            }
+        },
+        Ok(ProgramArguments { task: Task::Grade(grid), .. }) => {
+            if !grid.check_grid() {
+                eprintln!("Invalid arguments: the grid doesn't follow the rules of sudoku.");
+                std::process::exit(EXIT_INVALID_GRID)
+            }
+            println!("{}", grade(&grid))
+        },
+        Ok(ProgramArguments { task: Task::Explain(grid), unicode, quiet, .. }) => {
+            match explain(&grid) {
+                Ok((steps, solved_grid)) => {
+                    for step in steps {
+                        println!("{}", step)
                     }
+                    diagnostic(quiet, "Solved the given grid! Here it is:");
+                    print_drawn_grid(&draw_grid(&solved_grid, unicode))
+                },
+                Err(err) => exit_with_failure(&err)
+            }
+        },
+        Ok(ProgramArguments { task: Task::Interactive(grid), max_iterations, output, delimiter, out_file, unicode, .. }) => {
+            if !grid.check_grid() {
+                eprintln!("Invalid arguments: the grid doesn't follow the rules of sudoku.");
+                std::process::exit(EXIT_INVALID_GRID)
+            }
+            match interactive::run(grid, max_iterations) {
+                Ok(grid) => match (out_file, output) {
+                    (Some(path), _) => write_grid(&path, &grid, output, delimiter),
+                    (None, OutputFormat::Grid) => print_drawn_grid(&draw_grid(&grid, unicode)),
+                    (None, _) => println!("{}", format_grid(&grid, output, delimiter).trim_end())
+                },
+                Err(err) => {
+                    eprintln!("Interactive mode failed: {}", err);
+                    std::process::exit(EXIT_ARGUMENTS)
                 }
             }
-            else { // We're currently in the case where we got to a dead end earlier and we're trying to go back and change the previous digits
-                // Digit that the cell currently holds
-                let current_value = solved_grid.get(x, y);
-
-                let mut invalid = true;
-                // Iterate through all the digits, if we can't satisfy the conditions we need to go back even further.
-                for value in current_value..=9 {
-                    if solved_grid.check(x, y, value) {
-                        invalid = false;
-                        solved_grid.set(x, y, value);
-                        break
+        },
+        Ok(ProgramArguments { task: Task::Samurai(grid), max_iterations, out_file, quiet, .. }) => {
+            diagnostic(quiet, &format!("Samurai grid:\n{}", grid));
+            match solve_samurai(*grid, max_iterations) {
+                Ok(solved_grid) => match out_file {
+                    Some(path) => if let Err(err) = std::fs::write(&path, solved_grid.to_string()) {
+                        eprintln!("Couldn't write the grid to '{}': {}", path, err);
+                        std::process::exit(EXIT_ARGUMENTS)
+                    },
+                    None => {
+                        diagnostic(quiet, "Solved the given grid! Here it is:");
+                        print!("{}", solved_grid)
                     }
-                }
+                },
+                Err(err) => exit_with_failure(&err)
+            }
+        },
+        Ok(ProgramArguments { task: Task::Verify { puzzle, solution }, .. }) => {
+            if solution.is_solution_of(&puzzle) {
+                println!("OK");
+                return
+            }
 
-                if invalid {
-                    // We go back again so we reset this value to its original state
-                    solved_grid.set(x, y, 0);
-                    // Common block: back
-                    if x == 0 {
-                        if y > 0 {
-                            x = 8;
-                            y -= 1;
-                        } else {
-                            return Err(SudokuSolvingError::Unsolvable)
-                        }
-                    } else {
-                        x -= 1
-                    }
-                } else {
-                    iterating_forward = true;
-
-                    // Common block: forward
-                    if x >= 8 {
-                        if y >= 8 {
-                            break;
-                        } else {
-                            x = 0;
-                            y += 1;
-                        }
-                    } else {
-                        x += 1;
-                    }
+            // Clues that were changed are reported first, since they make the rest of the solution irrelevant
+            match puzzle.diff(&solution).into_iter().find(|&(_, _, clue, _)| clue != 0) {
+                Some((x, y, clue, value)) => println!("Mismatch at {}: the puzzle gives {}, the solution holds {}.", cell_ref(x, y), clue, value),
+                None => match (solution.validate(), solution.empty_cells().first()) {
+                    (Err(conflict), _) => println!("Mismatch: the solution breaks the rules, {}.", conflict),
+                    (Ok(()), Some(&(x, y))) => println!("Mismatch: the solution is incomplete, {} is empty.", cell_ref(x, y)),
+                    (Ok(()), None) => println!("Mismatch: the solution isn't valid.")
                 }
             }
-        } else { // There is a preset digit at the current position, we continue forward or go back depending on the direction we were going before.
-            if iterating_forward {
-                // Common block: forward
-                if x >= 8 {
-                    if y >= 8 {
-                        break;
-                    } else {
-                        x = 0;
-                        y += 1;
-                    }
-                } else {
-                    x += 1;
-                }
-            } else {
-                // Common block: back
-                if x == 0 {
-                    if y > 0 {
-                        x = 8;
-                        y -= 1;
-                    } else {
-                        return Err(SudokuSolvingError::Unsolvable)
-                    }
-                } else {
-                    x -= 1
-                }
+            std::process::exit(EXIT_ARGUMENTS)
+        },
+        Ok(ProgramArguments { task: Task::Validate(grid), constraints, .. }) => {
+            if !grid.check_grid_with(&constraints) {
+                std::process::exit(EXIT_INVALID_GRID)
+            }
+        },
+        Ok(ProgramArguments { task: Task::Solve(grid), max_iterations, timeout, json_full, worksheet: print_worksheet, output, delimiter, out_file, constraints, solver, color, unicode, candidates, quiet, stats: show_stats, .. }) => {
+            if json_full {
+                println!("{}", json_full_report(grid, &solve_options(max_iterations, timeout)));
+                return
             }
-        }
-
-        iteration_count += 1;
-    }
-
-    // The sudoku couldn't be solved because it probably got into an infinite loop somewhere
-    if iteration_count == max_iterations {
-        return Err(SudokuSolvingError::IterationCountOverflow)
-    }
-
-    Ok(solved_grid)
-}
-
-const MAX_ITERATIONS_DEFAULT: u32 = 1000000;
-
-/// Parses the program arguments using clap into a Result that either holds a tuple of our two arguments or a String describing an error.
-/// TODO: Better error handling/description.
-fn parse_arguments() -> Result<(SudokuGrid, u32), String> {
-    let matches = Command::new("SudokuSolver")
-        .about("Solves Sudoku puzzles!")
-        .arg(
-            arg!(--templates "Lists all the available sudoku grid templates.")
-                .required(false)
-        )
-        .arg(
-            Arg::new("grid")
-                .short('g')
-                .long("grid")
-                .value_name("TEMPLATE | DATA | FILE")
-                .help("Name of template, direct or file data (numbers separated by commas) of the sudoku grid to solve.")
-                .required_unless_present("templates")
-        )
-        .arg(
-            arg!(--max_solving_iterations <MAX_ITERATIONS> "Maximum number of iterations before the solving process gives up (default is 1000000).")
-                .required(false)
-                .value_parser(value_parser!(u32).range(1..))
-        ).get_matches();
-
-    // Print the available templates
-    if matches.get_flag("templates") {
-        println!("Here are the available templates:");
-        println!("'example': a hard-coded example sudoku grid.");
-        println!("'random': a randomly generated valid grid.");
 
-        return Err(String::new())
-    }
+            if candidates {
+                diagnostic(quiet, "Amount of candidates of each empty cell ('*' for a naked single, '!' for none, '#' for a filled cell):");
+                print_drawn_grid(&grid.to_candidate_counts_string());
+                return
+            }
 
-    let grid = matches.get_one::<String>("grid").map(|info| {
-        // We first check for templates
-        match info.as_str() {
-            "example" => Some(SudokuGrid::example_grid()),
-            "random" => Some(SudokuGrid::valid_random()),
-            _ => {
-                // Then for row data
-                let data = Regex::new(r"(\d,?)+")
-                    .ok()// We're only interested into the regex
-                    .map(|regex| regex.find(info))// We obtain the part we want
-                    .flatten()// We flatten the option
-                    .map(|m| m.as_str().to_string())// We convert the match into an &str
-                    .or(read_data_from_file(info))// If there is no match, meaning a path might have been specified, we try reading the file.
-                    .map(|s| {
-                        // We split the resulting part
-                        let digits = s.split(',').collect::<Vec<&str>>();
-                        // We ensure that the content is of the right size
-                        if digits.len() != 81 {
-                            return None
-                        }
-                        // We map all the values in the vec from &str to u8
-                        Some(digits.iter().map(|s| s.parse().unwrap_or(0)).collect::<Vec<u8>>())
-                    }).flatten();
-
-                data.map(|v| SudokuGrid::from_data(&v))
+            if !print_worksheet {
+                diagnostic(quiet, &format!("String representation of the grid: {}", draw_grid(&grid, unicode)));
+                let (rows, columns, groups) = grid.completed_units();
+                diagnostic(quiet, &format!("Already complete: {} row(s), {} column(s) and {} group(s).", rows.len(), columns.len(), groups.len()));
+                diagnostic(quiet, "Lets try to solve this sudoku...");
             }
-        }
-    }).flatten().ok_or(String::from("grid info couldn't be parsed. Try using a template or directly specifying the grid data (with numbers between commas, like so: '0,6,4,8,0,0,1,0,...')."))?;
 
-    Ok((grid, matches.get_one::<u32>("max_solving_iterations").map(|&r| r).unwrap_or(MAX_ITERATIONS_DEFAULT)))
-}
+            let mut stats = SolveStats::default();
+            let result = match solver {
+                Solver::Backtrack if constraints.is_empty() => solve_with_stats(grid.clone(), &solve_options(max_iterations, timeout), &mut stats).map_err(|(err, _)| err),
+                Solver::Backtrack => solve_with_constraints(grid.clone(), &constraints, max_iterations),
+                Solver::Dlx => solve_dlx(grid.clone())
+            };
+            if show_stats {
+                eprintln!("{}", stats)
+            }
 
-/// Reads the content of a file at the path referred by a String.
-fn read_data_from_file(path: &String) -> Option<String> {
-    File::open(path)
-        .ok()// We don't care about the error
-        .map(|mut file| {
-            let mut content = String::new();
-            file.read_to_string(&mut content).ok();
-            content
-        })// Maps the file to its actual content
-        .map(|s| s.trim().replace(' ', "")) // Trims the content string and gets rid of useless whitespaces.
-}
+            if print_worksheet {
+                match result {
+                    Ok(solved_grid) => print!("{}", worksheet(&grid, &solved_grid)),
+                    Err(err) => exit_with_failure(&err)
+                }
+                return
+            }
 
-fn main() {
-    match parse_arguments() {
-        Ok((grid, max_iterations)) => {
-            println!("String representation of the grid: {}", grid);
-            println!("Lets try to solve this sudoku...");
-            match solve(grid, max_iterations) {
-                Ok(solved_grid) => println!("Solved the given grid! Here it is: {}", solved_grid),
-                Err(err) => println!("Failed to solve the sudoku: {}", err)
+            match result {
+                Ok(solved_grid) => match (out_file, output) {
+                    (Some(path), _) => write_grid(&path, &solved_grid, output, delimiter),
+                    (None, OutputFormat::Grid) => {
+                        diagnostic(quiet, "Solved the given grid! Here it is:");
+                        print_drawn_grid(&if color { solved_grid.to_colored_string(&grid) } else { draw_grid(&solved_grid, unicode) })
+                    },
+                    (None, _) => println!("{}", format_grid(&solved_grid, output, delimiter).trim_end())
+                },
+                Err(err) => exit_with_failure(&err)
             }
         },
         Err(err) => {
             // empty error means no error
             if !err.is_empty() {
-                println!("Invalid arguments: {}", err)
+                eprintln!("Invalid arguments: {}", err);
+                std::process::exit(EXIT_ARGUMENTS)
             }
         }
     }
-}
\ No newline at end of file
+}
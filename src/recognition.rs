@@ -0,0 +1,82 @@
+//! Recognition of grids from images, such as photos or screenshots of a puzzle.
+//!
+//! No recognition is bundled: `GridRecognizer` is the extension point, which any closure taking the path of the image implements too.
+//! `TranscriptRecognizer` reads a transcript of the image instead of the image itself, and the `ocr` feature adds `TesseractRecognizer`,
+//! which runs the `tesseract` program on the image.
+
+use std::path::Path;
+
+use crate::{SudokuGrid, parse_grid_info};
+#[cfg(feature = "ocr")]
+use crate::parse_line_grid;
+
+/// Reads the grid shown by an image.
+pub trait GridRecognizer {
+    /// Recognizes the grid of the image at `path`, or returns a message describing why it couldn't be recognized.
+    fn recognize(&self, path: &Path) -> Result<SudokuGrid, String>;
+}
+
+impl<F: Fn(&Path) -> Result<SudokuGrid, String>> GridRecognizer for F {
+    fn recognize(&self, path: &Path) -> Result<SudokuGrid, String> {
+        self(path)
+    }
+}
+
+/// Recognizer reading the grid from a transcript next to the image: the text file of the same name with a `.txt` extension (`photo.txt` for `photo.png`),
+/// in any format accepted by `parse_grid_info()`. It stands in for actual recognition when the grids were transcribed by hand or by another tool.
+pub struct TranscriptRecognizer;
+
+impl GridRecognizer for TranscriptRecognizer {
+    fn recognize(&self, path: &Path) -> Result<SudokuGrid, String> {
+        let transcript = path.with_extension("txt");
+        let data = std::fs::read_to_string(&transcript).map_err(|err| format!("couldn't read the transcript '{}' of the image: {}", transcript.display(), err))?;
+        parse_grid_info(data.trim()).map_err(|err| format!("the transcript '{}' of the image couldn't be parsed ({})", transcript.display(), err))
+    }
+}
+
+/// Recognizer running the `tesseract` OCR program on the image, enabled by the `ocr` feature.
+/// Only the digits and dots are kept from its output, which must then be a grid in the 81-character line format:
+/// the image should show every cell, with `0` or `.` in the empty ones, like a printed grid of the `line` or `sdk` output formats.
+/// Photos of paper puzzles need to locate the cells first, which is left to better recognizers.
+#[cfg(feature = "ocr")]
+pub struct TesseractRecognizer {
+    /// Name or path of the `tesseract` program.
+    pub program: String
+}
+
+#[cfg(feature = "ocr")]
+impl Default for TesseractRecognizer {
+    fn default() -> TesseractRecognizer {
+        TesseractRecognizer { program: String::from("tesseract") }
+    }
+}
+
+#[cfg(feature = "ocr")]
+impl GridRecognizer for TesseractRecognizer {
+    fn recognize(&self, path: &Path) -> Result<SudokuGrid, String> {
+        // Page segmentation mode 6 reads the image as a single block of text
+        let output = std::process::Command::new(&self.program)
+            .arg(path)
+            .args(["stdout", "--psm", "6", "-c", "tessedit_char_whitelist=0123456789."])
+            .output()
+            .map_err(|err| format!("couldn't run '{}': {}", self.program, err))?;
+        if !output.status.success() {
+            return Err(format!("'{}' failed: {}", self.program, String::from_utf8_lossy(&output.stderr).trim()))
+        }
+
+        let text: String = String::from_utf8_lossy(&output.stdout).chars().filter(|c| c.is_ascii_digit() || *c == '.').collect();
+        parse_line_grid(&text).ok_or(format!("the image should show 81 cells, but {} were recognized", text.len()))
+    }
+}
+
+/// Returns the recognizer of the images given to the program: `TesseractRecognizer` with the `ocr` feature, `TranscriptRecognizer` otherwise.
+#[cfg(feature = "ocr")]
+pub fn default_recognizer() -> Box<dyn GridRecognizer> {
+    Box::new(TesseractRecognizer::default())
+}
+
+/// Returns the recognizer of the images given to the program: `TesseractRecognizer` with the `ocr` feature, `TranscriptRecognizer` otherwise.
+#[cfg(not(feature = "ocr"))]
+pub fn default_recognizer() -> Box<dyn GridRecognizer> {
+    Box::new(TranscriptRecognizer)
+}
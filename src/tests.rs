@@ -3,13 +3,1709 @@ use super::*;
 #[test]
 fn solve_sudoku1() {
     let values = vec![0, 6, 0, 0, 0, 0, 9, 7, 0, 0, 3, 0, 8, 0, 4, 0, 0, 0, 2, 0, 0, 5, 9, 0, 0, 0, 0, 0, 7, 0, 0, 4, 0, 6, 0, 0, 0, 0, 5, 0, 0, 0, 1, 0, 0, 0, 0, 6, 0, 3, 0, 0, 8, 0, 0, 0, 0, 0, 5, 9, 0, 0, 1, 0, 0, 0, 1, 0, 7, 0, 3, 0, 0, 8, 1, 0, 0, 0, 0, 6, 0];
-    let grid = SudokuGrid::from_data(values.as_slice());
+    let grid = SudokuGrid::from_data(values.as_slice()).unwrap();
     let solved = {
         match solve(grid, MAX_ITERATIONS_DEFAULT) {
             Ok(grid) => grid,
             Err(err) => panic!("Couldn't solve the test sudoku 1: {}", err)
         }
     };
-    let expected = vec![8, 6, 4, 3, 1, 2, 9, 7, 5, 5, 3, 9, 8, 7, 4, 2, 1, 6, 2, 1, 7, 5, 9, 6, 3, 4, 8, 3, 7, 8, 9, 4, 1, 6, 5, 2, 4, 2, 5, 7, 6, 8, 1, 9, 3, 1, 9, 6, 2, 3, 5, 7, 8, 4, 7, 4, 3, 6, 5, 9, 8, 2, 1, 6, 5, 2, 1, 8, 7, 4, 3, 9, 9, 8, 1, 4, 2, 3, 5, 6, 7];
-    assert_eq!(solved.data, expected, "Expected grid and solved grid contents didn't match.")
-}
\ No newline at end of file
+    assert_eq!(solved.unit_sums(), Some(([45; 9], [45; 9], [45; 9])), "Every unit of the solved grid should sum to 45.");
+    assert!(solved.is_solved(), "The solved grid should be a valid solution: {:?}", solved);
+    assert!(values.iter().zip(&solved.data).all(|(&clue, &value)| clue == 0 || clue == value), "The clues should be kept in the solution.")
+}
+
+
+#[test]
+fn is_solved_requires_a_full_valid_grid() {
+    let solved = solve(SudokuGrid::builtin_puzzle("easy1").unwrap(), MAX_ITERATIONS_DEFAULT).unwrap_or_else(|err| panic!("Couldn't solve the easy puzzle: {}", err));
+    assert!(solved.is_solved(), "The solution should be solved.");
+    assert!(!SudokuGrid::empty().is_solved(), "An empty grid isn't solved.");
+
+    let mut missing = solved.clone();
+    missing.set(4, 4, 0);
+    assert!(!missing.is_solved(), "A grid with an empty cell isn't solved.");
+
+    // Repeating a digit in a row breaks the row, its column and its group
+    let mut repeated = solved.clone();
+    repeated.set(0, 0, solved.get(1, 0));
+    assert!(!repeated.is_solved(), "A grid with a repeated digit isn't solved.")
+}
+
+
+#[test]
+fn completed_units_of_partial_grid() {
+    let mut grid = SudokuGrid::from_data(&[8, 6, 4, 3, 1, 2, 9, 7, 5, 5, 3, 9, 8, 7, 4, 2, 1, 6, 2, 1, 7, 5, 9, 6, 3, 4, 8, 3, 7, 8, 9, 4, 1, 6, 5, 2, 4, 2, 5, 7, 6, 8, 1, 9, 3, 1, 9, 6, 2, 3, 5, 7, 8, 4, 7, 4, 3, 6, 5, 9, 8, 2, 1, 6, 5, 2, 1, 8, 7, 4, 3, 9, 9, 8, 1, 4, 2, 3, 5, 6, 7]).unwrap();
+    grid.set(4, 4, 0);
+    let (rows, columns, groups) = grid.completed_units();
+    assert_eq!(rows, vec![0, 1, 2, 3, 5, 6, 7, 8], "Row 4 should not be complete.");
+    assert_eq!(columns, vec![0, 1, 2, 3, 5, 6, 7, 8], "Column 4 should not be complete.");
+    assert_eq!(groups, vec![0, 1, 2, 3, 5, 6, 7, 8], "The center group should not be complete.");
+
+    let hexadoku = (0..256).map(|i| ((4 * (i / 16 % 4) + i / 64 + i % 16) % 16) as u8 + 1).collect::<Vec<u8>>();
+    let (rows, columns, groups) = SudokuGrid::from_data_with_size(16, &hexadoku).unwrap().completed_units();
+    assert!([rows, columns, groups].iter().all(|units| *units == (0..16).collect::<Vec<usize>>()), "Every unit of a solved hexadoku is complete.")
+}
+
+
+#[test]
+fn solve_without_validation() {
+    let options = SolveOptions::default().skip_validation(true);
+    let solved = match solve_with_options(SudokuGrid::example_grid(), &options) {
+        Ok(grid) => grid,
+        Err(err) => panic!("Couldn't solve the example grid without validation: {}", err)
+    };
+    assert_eq!(solved.completed_units().0.len(), 9, "Solving without validation should still produce a complete grid.")
+}
+
+
+#[test]
+fn builtin_puzzles_are_valid() {
+    for (name, _) in BUILTIN_PUZZLES {
+        match SudokuGrid::builtin_puzzle(name) {
+            Some(grid) => assert!(grid.check_grid(), "Built-in puzzle '{}' is invalid.", name),
+            None => panic!("Built-in puzzle '{}' is missing.", name)
+        }
+    }
+    assert!(SudokuGrid::builtin_puzzle("unknown").is_none(), "Unknown puzzle names shouldn't match.")
+}
+
+
+#[test]
+fn json_full_report_of_easy_puzzle() {
+    let grid = SudokuGrid::builtin_puzzle("easy1").unwrap();
+    let report = json_full_report(grid.clone(), &SolveOptions::default());
+    assert_eq!(report["puzzle"], json!(grid.rows()), "The report should hold the original puzzle.");
+    assert_eq!(report["unique"], json!(true), "The easy puzzle has a single solution.");
+    assert_eq!(report["difficulty"], json!("easy"), "The easy puzzle should be solvable with naked singles.");
+    assert_eq!(report["solution"].as_array().map(|rows| rows.len()), Some(9), "The report should hold the solution rows.");
+
+    // A nearly empty grid takes more iterations than allowed
+    let mut sparse = SudokuGrid::empty();
+    sparse.set(0, 0, 1);
+    let report = json_full_report(sparse, &SolveOptions::new(50));
+    assert!(report["error"].is_string() && report["unique"].is_null(), "The iteration cap should apply to the whole report.")
+}
+
+
+#[test]
+fn progress_counts_only_correct_entries() {
+    let puzzle = SudokuGrid::example_grid();
+    let solution = solve(puzzle.clone(), MAX_ITERATIONS_DEFAULT).unwrap_or_else(|err| panic!("Couldn't solve the example grid: {}", err));
+    assert_eq!(puzzle.progress_against(&puzzle, &solution), Some(0.0), "An untouched puzzle has no progress.");
+    assert_eq!(solution.progress_against(&puzzle, &solution), Some(1.0), "The solution is fully done.");
+
+    // The example grid has 51 empty cells, the first three being (2, 0), (3, 0) and (5, 0)
+    let mut attempt = puzzle.clone();
+    attempt.set(2, 0, solution.get(2, 0));
+    attempt.set(3, 0, solution.get(3, 0));
+    attempt.set(5, 0, solution.get(5, 0) % 9 + 1);
+    assert_eq!(attempt.progress_against(&puzzle, &solution), Some(2.0 / 51.0), "Only the correct entries should count.");
+    assert!(attempt.progress_against(&puzzle, &SudokuGrid::empty_with_size(16).unwrap()).is_none(), "Grids of different sizes can't be compared.")
+}
+
+
+#[test]
+fn generated_no_guess_puzzle_is_logically_solvable() {
+    let puzzle = match SudokuGrid::generate_no_guess(42, 10) {
+        Ok(puzzle) => puzzle,
+        Err(err) => panic!("Couldn't generate a puzzle: {}", err)
+    };
+    assert!(!puzzle.is_complete(), "The generated puzzle should have empty cells.");
+    assert!(solve_logical_only(&puzzle).is_some(), "The generated puzzle should be solvable without guessing.");
+    assert!(SudokuGrid::generate_no_guess(42, 10).is_ok_and(|replayed| replayed.data == puzzle.data), "The same seed should generate the same puzzle.")
+}
+
+
+#[test]
+fn total_candidates_shrinks_with_clues() {
+    assert_eq!(SudokuGrid::empty().total_candidates(), 81 * 9, "Every digit is a candidate of every cell of an empty grid.");
+
+    let puzzle = SudokuGrid::example_grid();
+    let mut propagated = puzzle.clone();
+    if let Err(err) = propagate_singles(&mut propagated) {
+        panic!("Couldn't propagate the example grid: {}", err)
+    }
+    assert!(propagated.total_candidates() < puzzle.total_candidates(), "Propagation should reduce the candidates.");
+    assert_eq!(propagated.total_candidates(), 0, "The example grid is solved by propagation.")
+}
+
+
+#[test]
+fn cell_references() {
+    assert_eq!(parse_cell_ref("R3C5"), Some((4, 2)));
+    assert_eq!(parse_cell_ref("r3c5"), Some((4, 2)));
+    assert_eq!(parse_cell_ref("C5R3"), Some((4, 2)));
+    assert_eq!(parse_cell_ref("R0C5"), None, "Rows start at 1.");
+    assert_eq!(parse_cell_ref("R3"), None, "A column is required.");
+    assert_eq!(cell_ref(4, 2), "R3C5");
+    assert_eq!(parse_cell_ref(&cell_ref(8, 0)), Some((8, 0)), "Formatting then parsing a reference should give back the same cell.")
+}
+
+
+#[test]
+fn solve_with_progress_returns_partial_grid() {
+    let puzzle = SudokuGrid::builtin_puzzle("escargot").unwrap();
+    match solve_with_progress(puzzle.clone(), &SolveOptions::new(100)) {
+        Ok(_) => panic!("The hard puzzle shouldn't be solved in 100 iterations."),
+        Err((SudokuSolvingError::IterationCountOverflow(carried), partial)) => {
+            assert_eq!(carried.diff(&partial), vec![], "The error should carry the partial grid.");
+            assert!(partial.data.iter().filter(|&&v| v != 0).count() > puzzle.data.iter().filter(|&&v| v != 0).count(), "The partial grid should hold more digits than the puzzle.");
+            assert!(partial.check_grid(), "The partial grid should still respect the rules.")
+        },
+        Err((err, _)) => panic!("Unexpected error: {}", err)
+    }
+}
+
+
+#[test]
+fn flat_string_uses_delimiter() {
+    let line = SudokuGrid::example_grid().to_flat_string(';');
+    assert!(line.starts_with("5;3;0;0;7;0;0;0;0;6;"), "Unexpected flat output: {}", line);
+    assert_eq!(line.split(';').count(), 81, "The flat output should hold 81 values.")
+}
+
+
+#[test]
+fn solve_complete_grid() {
+    let solution = solve(SudokuGrid::example_grid(), MAX_ITERATIONS_DEFAULT).unwrap_or_else(|err| panic!("Couldn't solve the example grid: {}", err));
+    assert!(solution.is_solved(), "The solution should be solved.");
+    match solve(solution.clone(), 1) {
+        Ok(grid) => assert_eq!(grid.diff(&solution), vec![], "A solved grid should be returned as is."),
+        Err(err) => panic!("A solved grid should be accepted right away: {}", err)
+    }
+
+    // Swapping two digits of the last row keeps the grid complete but breaks the columns
+    let mut invalid = solution.clone();
+    invalid.set(7, 8, solution.get(8, 8));
+    invalid.set(8, 8, solution.get(7, 8));
+    assert!(!invalid.is_solved(), "The altered grid shouldn't be solved.");
+    assert!(matches!(solve(invalid, MAX_ITERATIONS_DEFAULT), Err(SudokuSolvingError::InvalidGrid(_))), "A complete but invalid grid should be rejected.")
+}
+
+
+#[test]
+fn poked_grid_stays_solvable() {
+    let solution = SudokuGrid::random_solution(&mut StdRng::seed_from_u64(7));
+    let puzzle = solution.poke_holes(55, 7);
+    assert_eq!(puzzle.empty_count(), 55, "55 clues should have been removed.");
+    assert!(count_solutions(&puzzle, 1) >= 1, "The poked grid should stay solvable.");
+
+    let hexadoku = (0..256).map(|i| ((4 * (i / 16 % 4) + i / 64 + i % 16) % 16) as u8 + 1).collect::<Vec<u8>>();
+    let hexadoku = SudokuGrid::from_data_with_size(16, &hexadoku).unwrap();
+    assert_eq!(hexadoku.poke_holes(200, 7).empty_count(), 200, "Clues should be removed anywhere in a hexadoku.")
+}
+
+
+#[test]
+fn batch_report_statistics() {
+    let mut invalid = SudokuGrid::example_grid();
+    invalid.set(2, 0, 5);
+    let puzzles = vec![SudokuGrid::example_grid(), invalid, SudokuGrid::builtin_puzzle("example2").unwrap()];
+
+    let (results, report) = solve_batch(puzzles, &SolveOptions::default());
+    assert!(results[0].is_ok() && results[1].is_err() && results[2].is_ok(), "The results should follow the batch order.");
+    assert_eq!((report.total, report.solved, report.failed), (3, 2, 1));
+    assert!(report.max_iterations > 0 && report.total_iterations >= report.max_iterations as u64, "The iterations should be accumulated.");
+    assert_eq!(report.avg_iterations, report.total_iterations as f64 / 3.0);
+
+    // Streaming the batch, with an unreadable puzzle and a stop at the first ambiguous one
+    let mut ambiguous = SudokuGrid::example_grid();
+    for (x, y) in [(0, 0), (1, 0), (4, 0), (0, 1)] {
+        ambiguous.set(x, y, 0)
+    }
+    let puzzles = vec![Ok(SudokuGrid::example_grid()), Err("unreadable"), Ok(ambiguous), Ok(SudokuGrid::example_grid())];
+    let mut handled = Vec::new();
+    let report = solve_batch_with(puzzles, &SolveOptions::default().require_unique(true), |result| {
+        let keep_going = !matches!(result, Ok(Err(SudokuSolvingError::MultipleSolutions)));
+        handled.push(result.map(|result| result.is_ok()));
+        keep_going
+    });
+    assert_eq!(handled, vec![Ok(true), Err("unreadable"), Ok(false)], "The batch should stop once the handler asks to.");
+    assert_eq!((report.total, report.solved, report.failed), (3, 1, 2))
+}
+
+
+#[test]
+fn symmetric_count_matches_plain_count() {
+    // This solution is transformed into itself by a half-turn rotation followed by a relabeling of its digits
+    let solution: Vec<u8> = (0..81).map(|i| ((i / 9 * 3 + i / 27 + i % 9) % 9) as u8 + 1).collect();
+    let mut puzzle = SudokuGrid::from_data(&solution).unwrap();
+    // Removing cells in symmetric pairs keeps the symmetry
+    for i in [2, 3, 4, 6, 8, 9, 16, 17, 18, 19, 22, 23, 24, 25, 26, 28, 30, 31, 32, 33, 34, 35, 37, 39, 40] {
+        puzzle.set(i % 9, i / 9, 0);
+        puzzle.set((80 - i) % 9, (80 - i) / 9, 0)
+    }
+    assert!(symmetry_relabeling(&puzzle, Symmetry::Rotational180).is_some(), "The test puzzle should be symmetric.");
+
+    let plain = count_solutions(&puzzle, 100000);
+    assert_eq!(plain, 21, "The test puzzle should have 21 solutions.");
+    assert_eq!(count_solutions_with_symmetry(&puzzle, 100000, Symmetry::Rotational180), plain, "Both counts should match.");
+    assert_eq!(count_solutions_with_symmetry(&puzzle, 2, Symmetry::Rotational180), 2, "The limit should still be respected.");
+
+    // An asymmetric grid falls back to the plain count
+    let example = SudokuGrid::example_grid();
+    assert_eq!(count_solutions_with_symmetry(&example, 2, Symmetry::Rotational180), count_solutions(&example, 2));
+
+    // So does a hexadoku, which the symmetries of a 9x9 grid don't apply to
+    let hexadoku = SudokuGrid::empty_with_size(16).unwrap();
+    assert!(symmetry_relabeling(&hexadoku, Symmetry::Rotational180).is_none(), "Only 9x9 grids have a relabeling.");
+    assert_eq!(count_solutions_with_symmetry(&hexadoku, 2, Symmetry::Rotational180), count_solutions(&hexadoku, 2))
+}
+
+
+#[test]
+fn cells_bucketed_by_candidate_count() {
+    let grid = SudokuGrid::example_grid();
+    let buckets: Vec<Vec<(usize, usize)>> = (0..=9).map(|k| grid.cells_with_candidate_count(k)).collect();
+    assert_eq!(buckets.iter().map(|cells| cells.len()).sum::<usize>(), 51, "Every empty cell should be in exactly one bucket.");
+    assert!(buckets[0].is_empty(), "The example grid has no dead end.");
+    assert!(buckets[1].contains(&(4, 4)), "The center cell of the example grid can only hold a 5.");
+    for &(x, y) in &buckets[1] {
+        assert_eq!(grid.candidates(x, y).len(), 1)
+    }
+}
+
+
+#[test]
+fn unit_sums_of_example_grid() {
+    let (rows, columns, groups) = SudokuGrid::example_grid().unit_sums().unwrap_or_else(|| panic!("The example grid is 9x9."));
+    assert_eq!(rows, [15, 21, 23, 17, 16, 15, 16, 19, 24]);
+    assert_eq!(columns, [30, 18, 8, 13, 33, 17, 2, 21, 24]);
+    assert_eq!(groups, [31, 22, 6, 19, 19, 10, 6, 22, 31]);
+    assert!(SudokuGrid::empty_with_size(16).is_ok_and(|hexadoku| hexadoku.unit_sums().is_none()), "The sums only cover 9x9 grids.")
+}
+
+
+#[test]
+fn random_puzzle_stream() {
+    let puzzles: Vec<SudokuGrid> = match random_puzzles(30, 3, 10).take(2).collect() {
+        Ok(puzzles) => puzzles,
+        Err(err) => panic!("Couldn't generate the puzzles of the stream: {}", err)
+    };
+    for puzzle in &puzzles {
+        assert_eq!(puzzle.filled_count(), 30, "Every puzzle should have 30 clues.");
+        assert_eq!(count_solutions(puzzle, 2), 1, "Every puzzle should be uniquely solvable.")
+    }
+    assert_ne!(puzzles[0].data, puzzles[1].data, "The stream should yield fresh puzzles.");
+
+    let replayed: Vec<SudokuGrid> = random_puzzles(30, 3, 10).take(2).flatten().collect();
+    assert!(replayed.len() == 2 && puzzles.iter().zip(&replayed).all(|(a, b)| a.data == b.data), "The same seed should yield the same stream.");
+
+    // Puzzles that can't be carved down to 17 clues in a single attempt are reported rather than ending the stream
+    let mut sparse = random_puzzles(17, 3, 1).take(3);
+    assert!(sparse.all(|puzzle| matches!(puzzle, Err(GenError::TargetNotMet { .. }))), "A 17-clue puzzle is rarely found in a single attempt.")
+}
+
+
+#[test]
+fn solve_timing_breakdown() {
+    let puzzle = SudokuGrid::builtin_puzzle("example2").unwrap();
+
+    let mut stats = SolveStats::default();
+    let timed = SolveOptions::default().propagate(true).measure_timing(true);
+    assert!(solve_with_stats(puzzle.clone(), &timed, &mut stats).is_ok(), "The puzzle should be solved.");
+    assert!(stats.validation_ns > 0 && stats.propagation_ns > 0 && stats.search_ns > 0, "Every phase should be timed: {:?}", stats);
+
+    let mut untimed_stats = SolveStats::default();
+    assert!(solve_with_stats(puzzle, &SolveOptions::default(), &mut untimed_stats).is_ok(), "The puzzle should be solved.");
+    assert_eq!((untimed_stats.validation_ns, untimed_stats.propagation_ns, untimed_stats.search_ns), (0, 0, 0), "Timing should only be measured on request.")
+}
+
+
+#[test]
+fn contradictory_grids() {
+    assert!(!SudokuGrid::example_grid().is_contradictory(), "The example grid isn't contradictory.");
+
+    // The top-left cell sees every digit from 1 to 8 plus a 9 in its group: no candidate is left
+    let mut no_candidate = SudokuGrid::empty();
+    for x in 1..9 {
+        no_candidate.set(x, 0, x as u8)
+    }
+    no_candidate.set(1, 1, 9);
+    assert!(no_candidate.is_contradictory(), "A cell without candidates is a contradiction.");
+
+    // The 1s of the first two groups and of the columns 6 and 7 leave a single place for the 1 of the first row
+    let mut no_place = SudokuGrid::empty();
+    no_place.set(0, 1, 1);
+    no_place.set(3, 2, 1);
+    no_place.set(6, 3, 1);
+    no_place.set(7, 6, 1);
+    assert!(!no_place.is_contradictory(), "The first row can still get a 1 in its last cell.");
+    no_place.set(8, 0, 5);
+    assert!(no_place.is_contradictory(), "A digit without any place in a unit is a contradiction.")
+}
+
+
+#[test]
+fn solve_fixed_array() {
+    let mut cells = [0u8; 81];
+    cells.copy_from_slice(&SudokuGrid::example_grid().data);
+
+    let solved = match solve_array(cells, MAX_ITERATIONS_DEFAULT) {
+        Ok(solved) => solved,
+        Err(err) => panic!("Couldn't solve the example array: {}", err)
+    };
+    assert!(SudokuGrid::from_data(&solved).unwrap().is_solved(), "The solved array should be a valid solution.");
+    assert!(cells.iter().zip(solved.iter()).all(|(&given, &value)| given == 0 || given == value), "The givens should be kept.");
+
+    cells[1] = 5;
+    assert!(matches!(solve_array(cells, MAX_ITERATIONS_DEFAULT), Err(SudokuSolvingError::InvalidGrid(_))), "A duplicated digit should be rejected.")
+}
+
+
+#[test]
+fn solved_cells_provenance() {
+    let puzzle = SudokuGrid::builtin_puzzle("example2").unwrap();
+    let mut stats = SolveStats::default();
+    let solved = match solve_with_stats(puzzle.clone(), &SolveOptions::default().propagate(true), &mut stats) {
+        Ok(grid) => grid,
+        Err((err, _)) => panic!("Couldn't solve the puzzle: {}", err)
+    };
+
+    let mut derived = puzzle.clone();
+    if let Err(err) = propagate_singles(&mut derived) {
+        panic!("Couldn't propagate the puzzle: {}", err)
+    }
+    for i in 0..81 {
+        let expected = if puzzle.data[i] != 0 {
+            PROVENANCE_GIVEN
+        } else if derived.data[i] != 0 {
+            PROVENANCE_DERIVED
+        } else {
+            PROVENANCE_GUESSED
+        };
+        assert_eq!(stats.provenance[i], expected, "Unexpected provenance for cell {} holding {}.", i, solved.data[i])
+    }
+    assert!(stats.provenance.contains(&PROVENANCE_GUESSED), "This puzzle needs guessing.")
+}
+
+
+#[test]
+fn nonsensical_clue_counts_are_rejected() {
+    for clues in [0, 5, 16, 81, 100] {
+        assert!(matches!(random_puzzles(clues, 0, 10).next(), Some(Err(GenError::InvalidClueCount(c))) if c == clues), "{} clues should be rejected.", clues)
+    }
+    assert!(validate_clue_count(17).is_ok() && validate_clue_count(80).is_ok(), "The bounds should be accepted.");
+    assert!(GenError::InvalidClueCount(5).to_string().contains("at least 17 clues"), "The error should explain the 17-clue minimum.")
+}
+
+
+#[test]
+fn labeled_string_layout() {
+    let labeled = SudokuGrid::example_grid().to_labeled_string().unwrap_or_default();
+    let lines: Vec<&str> = labeled.lines().collect();
+    assert_eq!(lines.len(), 14, "The header, 4 borders and 9 rows are expected.");
+    assert_eq!(lines[0], "    123   456   789");
+    assert_eq!(lines[1], "  |-----------------|");
+    assert_eq!(lines[2], "1 | 53_ | _7_ | ___ |");
+    assert_eq!(lines[13], "  |-----------------|");
+    assert!(SudokuGrid::empty_with_size(16).is_ok_and(|hexadoku| hexadoku.to_labeled_string().is_none()), "The labels only fit 9x9 grids.")
+}
+
+
+#[test]
+fn solve_with_assumption() {
+    let grid = SudokuGrid::example_grid();
+    let solution = solve(grid.clone(), MAX_ITERATIONS_DEFAULT).unwrap_or_else(|err| panic!("Couldn't solve the example grid: {}", err));
+
+    match solve_assuming(&grid, 2, 0, solution.get(2, 0), MAX_ITERATIONS_DEFAULT) {
+        Ok(solved) => assert_eq!(solved.diff(&solution), vec![], "The right assumption leads to the solution."),
+        Err(err) => panic!("The right assumption should be solvable: {}", err)
+    }
+    assert_eq!(grid.get(2, 0), 0, "The caller's grid shouldn't be mutated.");
+
+    assert!(matches!(solve_assuming(&grid, 2, 0, 5, MAX_ITERATIONS_DEFAULT), Err(SudokuSolvingError::InvalidGrid(_))), "A 5 is already in the row.");
+    assert!(matches!(solve_assuming(&grid, 0, 0, 1, MAX_ITERATIONS_DEFAULT), Err(SudokuSolvingError::InvalidGrid(_))), "The cell already holds a 5.");
+    let wrong = (1..=9).find(|&v| v != solution.get(2, 0) && grid.check(2, 0, v)).unwrap();
+    assert!(matches!(solve_assuming(&grid, 2, 0, wrong, MAX_ITERATIONS_DEFAULT), Err(SudokuSolvingError::Unsolvable)), "A wrong but legal assumption is unsolvable.")
+}
+
+
+#[test]
+fn worksheet_layout() {
+    let puzzle = SudokuGrid::example_grid();
+    let solution = solve(puzzle.clone(), MAX_ITERATIONS_DEFAULT).unwrap_or_else(|err| panic!("Couldn't solve the example grid: {}", err));
+    let sheet = worksheet(&puzzle, &solution);
+    assert!(sheet.starts_with(&format!("Puzzle{}", puzzle)), "The puzzle should come first.");
+    assert!(sheet.ends_with(&format!("Answer Key{}", solution)), "The answer key should come last.")
+}
+
+
+#[test]
+fn branching_factor() {
+    assert_eq!(SudokuGrid::builtin_puzzle("easy1").unwrap().average_branching(), 0.0, "The easy puzzle doesn't need any branching.");
+    assert!(SudokuGrid::builtin_puzzle("example2").unwrap().average_branching() >= 2.0, "Every cell left by propagation has at least 2 candidates.")
+}
+
+
+#[test]
+fn most_reducing_clue_of_ambiguous_grid() {
+    let mut grid = SudokuGrid::example_grid();
+    // Removing these clues leaves the example grid with several solutions
+    for (x, y) in [(0, 0), (1, 0), (4, 0), (0, 1), (3, 1), (4, 1)] {
+        grid.set(x, y, 0)
+    }
+    let count = count_solutions(&grid, 1000);
+    assert_eq!(count, 64, "The test grid should be ambiguous.");
+
+    let (x, y, value, remaining) = most_reducing_clue(&grid, 1000).unwrap_or_else(|| panic!("A clue should be found."));
+    assert!(remaining >= 1 && remaining < count, "The clue should reduce the solutions.");
+    let mut with_clue = grid.clone();
+    with_clue.set(x, y, value);
+    assert_eq!(count_solutions(&with_clue, 1000), remaining, "The reported count should match the grid with the clue.");
+
+    assert!(most_reducing_clue(&SudokuGrid::example_grid(), 1000).is_none(), "A unique grid needs no clue.");
+
+    // A hexadoku whose first two rows are empty, which can be swapped column by column
+    let hexadoku = (0..256).map(|i| if i < 32 { 0 } else { ((4 * (i / 16 % 4) + i / 64 + i % 16) % 16) as u8 + 1 }).collect::<Vec<u8>>();
+    let hexadoku = SudokuGrid::from_data_with_size(16, &hexadoku).unwrap();
+    assert!(most_reducing_clue(&hexadoku, 1000).is_some_and(|(x, y, value, _)| x < 16 && y < 2 && value <= 16), "Hexadokus should be handled too.")
+}
+
+
+#[test]
+fn solve_sandwich_sudoku() {
+    let solution = [8, 6, 4, 3, 1, 2, 9, 7, 5, 5, 3, 9, 8, 7, 4, 2, 1, 6, 2, 1, 7, 5, 9, 6, 3, 4, 8, 3, 7, 8, 9, 4, 1, 6, 5, 2, 4, 2, 5, 7, 6, 8, 1, 9, 3, 1, 9, 6, 2, 3, 5, 7, 8, 4, 7, 4, 3, 6, 5, 9, 8, 2, 1, 6, 5, 2, 1, 8, 7, 4, 3, 9, 9, 8, 1, 4, 2, 3, 5, 6, 7];
+    let sandwich = Sandwich::parse("2 21 12 4 0 0 10 22 8\n13 9 31 15 7 13 11 9 0").unwrap_or_else(|| panic!("The sandwich sums should be parsed."));
+    let constraints: Vec<Box<dyn Constraint>> = vec![Box::new(sandwich)];
+
+    // The top three rows are left to the sandwich sums
+    let mut grid = SudokuGrid::from_data(&solution).unwrap();
+    for i in 0..27 {
+        grid.set(i % 9, i / 9, 0)
+    }
+    assert_eq!(count_solutions(&grid, 1000), 192, "The grid alone should be ambiguous.");
+    match solve_with_constraints(grid, &constraints, MAX_ITERATIONS_DEFAULT) {
+        Ok(solved) => assert_eq!(solved.data[..], solution, "The sandwich sums should lead to the expected solution."),
+        Err(err) => panic!("Couldn't solve the sandwich sudoku: {}", err)
+    }
+
+    let mut broken = SudokuGrid::empty();
+    broken.set(0, 0, 1);
+    broken.set(1, 0, 5);
+    broken.set(2, 0, 9);
+    assert!(broken.check_grid(), "The broken grid should follow the classic rules.");
+    assert!(!broken.check_grid_with(&constraints), "A 5 between the 1 and the 9 should break the sum of the first row.");
+    assert!(SudokuGrid::empty().check_grid_with(&constraints), "An empty sandwich grid should be valid.");
+
+    let partial = Sandwich::parse("-,-,-,-,-,-,-,-,-,-,-,-,-,-,-,-,-,5").unwrap_or_else(|| panic!("Missing clues should be parsed."));
+    assert_eq!((partial.rows[0], partial.columns[8]), (None, Some(5)));
+    assert!(Sandwich::parse("1 2 3").is_none(), "There should be exactly 18 sums.");
+    assert!(Sandwich::parse(&"36 ".repeat(18)).is_none(), "Sums above 35 can't be sandwiched.")
+}
+
+
+#[test]
+fn pencilmark_string_of_example_grid() {
+    let pencilmarks = SudokuGrid::example_grid().to_pencilmark_string().unwrap();
+    let lines = pencilmarks.lines().collect::<Vec<&str>>();
+    assert_eq!(lines.len(), 37, "There should be 3 lines per row, a separator after every row and a top border.");
+    assert!(lines.iter().all(|line| line.len() == 43), "Every line should have the same width.");
+
+    // R1C1 holds a 5 and R1C3 has the candidates 1, 2 and 4
+    assert_eq!(&lines[1][2..13], ".-. .-. 12 ");
+    assert_eq!(&lines[2][2..13], "|5| |3| 4  ");
+    assert_eq!(&lines[3][2..13], "'-' '-'    ");
+    assert_eq!(lines[4], "|             |             |             |")
+}
+
+
+#[test]
+fn generation_gives_up_after_max_attempts() {
+    let mut rng = StdRng::seed_from_u64(7);
+    // A single attempt practically never carves a random solution down to the minimum
+    match SudokuGrid::generate_with_clues(MIN_UNIQUE_CLUES, &mut rng, 1) {
+        Err(GenError::TargetNotMet { best }) => {
+            assert!(best.data.iter().filter(|&&v| v != 0).count() > MIN_UNIQUE_CLUES, "The best puzzle should miss the target.");
+            assert_eq!(count_solutions(&best, 2), 1, "The best puzzle should still be uniquely solvable.")
+        },
+        Err(err) => panic!("Unexpected generation error: {}", err),
+        Ok(_) => panic!("A 17-clue puzzle shouldn't be found in a single attempt.")
+    }
+}
+
+
+#[test]
+fn parse_boxed_grid_round_trip() {
+    let grid = SudokuGrid::example_grid();
+    let parsed = parse_grid_info(&grid.to_string()).unwrap_or_else(|err| panic!("The boxed grid should be parsed: {}", err));
+    assert_eq!(parsed.diff(&grid), vec![], "The grid should survive a round trip through its boxed output.");
+
+    let mut truncated = grid.to_string();
+    truncated.truncate(truncated.len() / 2);
+    assert!(parse_boxed_grid(&truncated).is_none(), "A partial boxed grid should be rejected.")
+}
+
+
+#[test]
+fn cells_seeing_each_other() {
+    let grid = SudokuGrid::empty();
+    assert!(grid.sees((0, 0), (8, 0)), "Cells of the same row should see each other.");
+    assert!(grid.sees((4, 1), (4, 7)), "Cells of the same column should see each other.");
+    assert!(grid.sees((3, 3), (5, 5)), "Cells of the same group should see each other.");
+    assert!(!grid.sees((0, 0), (4, 4)), "Unrelated cells shouldn't see each other.");
+    assert!(!grid.sees((2, 2), (2, 2)), "A cell shouldn't see itself.");
+
+    // Every cell sees 20 others: 8 in its row, 8 in its column and 4 more in its group
+    let seen = (0..81).filter(|&i| grid.sees((4, 4), (i % 9, i / 9))).count();
+    assert_eq!(seen, 20);
+
+    // In a hexadoku, 15 in its row, 15 in its column and 9 more in its 4x4 group
+    let hexadoku = SudokuGrid::empty_with_size(16).unwrap();
+    assert!(hexadoku.sees((0, 0), (3, 3)) && !hexadoku.sees((0, 0), (4, 4)), "The groups of a hexadoku are 4x4.");
+    assert_eq!((0..256).filter(|&i| hexadoku.sees((5, 5), (i % 16, i / 16))).count(), 39)
+}
+
+
+#[test]
+fn rust_literal_matches_example_layout() {
+    let literal = SudokuGrid::example_grid().to_rust_literal();
+    let lines: Vec<&str> = literal.lines().collect();
+    assert_eq!(lines.len(), 13, "The literal should span 9 rows, 2 blank lines and the brackets.");
+    assert_eq!(lines[0], "vec![");
+    assert_eq!(lines[1], "    5, 3, 0,   0, 7, 0,   0, 0, 0,");
+    assert_eq!(lines[4], "");
+    assert_eq!(lines[11], "    0, 0, 0,   0, 8, 0,   0, 7, 9");
+    assert_eq!(lines[12], "]");
+
+    // A hexadoku has 4 groups of 4 values per row, and 4 bands of rows
+    let literal = SudokuGrid::empty_with_size(16).unwrap().to_rust_literal();
+    let lines: Vec<&str> = literal.lines().collect();
+    assert_eq!(lines.len(), 21, "The literal should span 16 rows, 3 blank lines and the brackets.");
+    assert_eq!(lines[1], format!("    {},", ["0, 0, 0, 0"; 4].join(",   ")));
+    assert_eq!(lines[5], "")
+}
+
+
+#[test]
+fn guess_depth_limit() {
+    // The example grid only needs singles
+    let no_guess = SolveOptions::default().max_guess_depth(0);
+    assert!(solve_with_options(SudokuGrid::example_grid(), &no_guess).is_ok(), "The example grid should be solved without guessing.");
+
+    let escargot = SudokuGrid::builtin_puzzle("escargot").unwrap();
+    assert!(matches!(solve_with_options(escargot.clone(), &no_guess), Err(SudokuSolvingError::GuessDepthExceeded)), "AI Escargot can't be solved without guessing.");
+
+    let expected = solve(escargot.clone(), MAX_ITERATIONS_DEFAULT).map(|grid| grid.data).unwrap_or_default();
+    let deep = SolveOptions::default().max_guess_depth(81);
+    assert!(solve_with_options(escargot, &deep).is_ok_and(|grid| grid.data == expected), "A deep enough search should find the solution.")
+}
+
+
+#[test]
+fn solve_propagates_singles_first() {
+    // The example grid only needs singles, so no search iteration is left to do
+    let mut stats = SolveStats::default();
+    assert!(solve_with_stats(SudokuGrid::example_grid(), &SolveOptions::default(), &mut stats).is_ok(), "The example grid should be solved.");
+    assert_eq!(stats.iterations, 0, "Propagation alone should solve the example grid.");
+
+    let mut backtracking_stats = SolveStats::default();
+    let backtracking_only = SolveOptions::default().propagate(false);
+    assert!(solve_with_stats(SudokuGrid::example_grid(), &backtracking_only, &mut backtracking_stats).is_ok(), "The example grid should be solved.");
+    assert!(backtracking_stats.iterations > 0, "Pure backtracking should need iterations.");
+
+    // The top-left cell has no candidate left, which propagation detects right away
+    let mut contradictory = SudokuGrid::empty();
+    for x in 1..9 {
+        contradictory.set(x, 0, x as u8)
+    }
+    contradictory.set(1, 1, 9);
+    assert!(matches!(solve(contradictory, MAX_ITERATIONS_DEFAULT), Err(SudokuSolvingError::Unsolvable)), "A contradiction should make the grid unsolvable.")
+}
+
+
+#[test]
+fn solve_unique_rejects_ambiguous_grids() {
+    assert!(solve_unique(SudokuGrid::example_grid(), MAX_ITERATIONS_DEFAULT).is_ok(), "The example grid has a single solution.");
+
+    let mut ambiguous = SudokuGrid::example_grid();
+    for (x, y) in [(0, 0), (1, 0), (4, 0), (0, 1)] {
+        ambiguous.set(x, y, 0)
+    }
+    assert_eq!(count_solutions(&ambiguous, 10), 2);
+    assert!(solve(ambiguous.clone(), MAX_ITERATIONS_DEFAULT).is_ok(), "solve() should still return one of the solutions.");
+    assert_eq!(count_solutions_with_options(&ambiguous, 10, &SolveOptions::default()).ok(), Some(2), "Both counts should match.");
+    assert!(matches!(solve_unique(ambiguous.clone(), MAX_ITERATIONS_DEFAULT), Err(SudokuSolvingError::MultipleSolutions)), "Several solutions should be rejected.");
+    let mut sparse = SudokuGrid::empty();
+    sparse.set(0, 0, 1);
+    assert!(matches!(solve_unique(sparse, 10), Err(SudokuSolvingError::IterationCountOverflow(_))), "The counting should respect the iteration cap.");
+
+    let mut invalid = SudokuGrid::example_grid();
+    invalid.set(2, 0, 5);
+    assert!(matches!(solve_unique(invalid, MAX_ITERATIONS_DEFAULT), Err(SudokuSolvingError::InvalidGrid(_))), "An invalid grid should still be reported as such.")
+}
+
+
+#[test]
+fn line_format_round_trip() {
+    let grid = SudokuGrid::example_grid();
+    let line = grid.to_line_string();
+    assert!(line.starts_with("53..7....6..195..."), "Empty cells should be dots: {}", line);
+
+    let parsed = parse_grid_info(&line).unwrap_or_else(|err| panic!("The line should be parsed: {}", err));
+    assert_eq!(parsed.diff(&grid), vec![], "The grid should survive a round trip through the line format.");
+
+    // Dots and zeros can be mixed
+    let mixed = line.replacen('.', "0", 10);
+    assert!(parse_grid_info(&mixed).is_ok_and(|parsed| parsed.data == grid.data), "Mixed empty markers should be accepted.");
+
+    let invalid = line.replacen('.', "q", 1);
+    assert_eq!(invalid.len(), 81);
+    assert!(parse_grid_info(&invalid).is_err(), "An invalid character should be rejected.")
+}
+
+
+#[test]
+fn bitmask_check_matches_unit_scan() {
+    let mut rng = StdRng::seed_from_u64(11);
+    let mut grid = SudokuGrid::example_grid();
+
+    // Random placements, including duplicates and erasures, must keep the masks in sync with the cells
+    for _ in 0..300 {
+        grid.set(rng.gen_range(0..9), rng.gen_range(0..9), rng.gen_range(0..=9));
+        for (x, y, value) in (0..81).flat_map(|i| (1..=9).map(move |value| (i % 9, i / 9, value))) {
+            let scanned = !grid.row(y).contains(&value) && !grid.column(x).contains(&value) && !grid.group(x, y).contains(&value);
+            assert_eq!(grid.check(x, y, value), scanned, "check() disagrees with the units for {} at {}.", value, cell_ref(x, y))
+        }
+    }
+
+    let mut stats = SolveStats::default();
+    let backtracking_only = SolveOptions::default().propagate(false);
+    assert!(solve_with_stats(SudokuGrid::builtin_puzzle("escargot").unwrap(), &backtracking_only, &mut stats).is_ok(), "The puzzle should be solved.");
+    assert!(stats.iterations > 100, "The puzzle should need a real search.")
+}
+
+
+#[test]
+#[ignore = "its timing depends on the machine, run it with `cargo test --release -- --ignored`"]
+fn backtracking_only_solving_is_fast() {
+    // A benchmark of sorts: pure backtracking on a hard puzzle should only take a fraction of a second
+    let start = Instant::now();
+    let backtracking_only = SolveOptions::default().propagate(false);
+    assert!(solve_with_options(SudokuGrid::builtin_puzzle("escargot").unwrap(), &backtracking_only).is_ok(), "The puzzle should be solved.");
+    assert!(start.elapsed() < Duration::from_secs(2), "The search took {:?}.", start.elapsed())
+}
+
+
+#[test]
+fn dlx_solves_what_backtracking_cannot() {
+    // A 17-clue puzzle built against brute force: its first row is empty and its solution starts with 987654321
+    let puzzle = parse_line_grid("..............3.85..1.2.......5.7.....4...1...9.......5......73..2.1........4...9").unwrap();
+    let capped_backtracking = SolveOptions::new(10000).propagate(false);
+    assert!(matches!(solve_with_options(puzzle.clone(), &capped_backtracking), Err(SudokuSolvingError::IterationCountOverflow(_))), "Backtracking should give up on this puzzle.");
+
+    let solved = match solve_dlx(puzzle.clone()) {
+        Ok(grid) => grid,
+        Err(err) => panic!("Couldn't solve the puzzle with dancing links: {}", err)
+    };
+    assert!(solved.is_solved(), "The solution should follow the rules.");
+    assert!(puzzle.data.iter().zip(&solved.data).all(|(&given, &value)| given == 0 || given == value), "The givens should be kept.");
+    assert_eq!(solved.row(0), vec![9, 8, 7, 6, 5, 4, 3, 2, 1]);
+
+    assert!(solve_dlx(SudokuGrid::example_grid()).is_ok_and(|grid| solve(SudokuGrid::example_grid(), MAX_ITERATIONS_DEFAULT).is_ok_and(|expected| expected.data == grid.data)), "Both solvers should agree.");
+
+    let mut invalid = SudokuGrid::example_grid();
+    invalid.set(8, 8, 7);
+    assert!(matches!(solve_dlx(invalid), Err(SudokuSolvingError::InvalidGrid(_))), "Clashing givens should be rejected.")
+}
+
+
+#[test]
+fn backtracking_branches_on_most_constrained_cell() {
+    let puzzle = SudokuGrid::builtin_puzzle("example2").unwrap();
+    let backtracking_only = SolveOptions::default().propagate(false);
+
+    let mut stats = SolveStats::default();
+    let solved = match solve_with_stats(puzzle.clone(), &backtracking_only, &mut stats) {
+        Ok(grid) => grid,
+        Err((err, _)) => panic!("Couldn't solve the puzzle: {}", err)
+    };
+    // Row-major backtracking used to take about 40000 iterations on this puzzle
+    assert!(stats.iterations < 1000, "MRV should cut the search down, took {} iterations.", stats.iterations);
+    assert!(solve_dlx(puzzle.clone()).is_ok_and(|expected| expected.data == solved.data), "The solution should be the unique one.");
+
+    assert!(matches!(solve_with_options(puzzle, &SolveOptions::new(10).propagate(false)), Err(SudokuSolvingError::IterationCountOverflow(_))), "The iteration cap should still hold.")
+}
+
+
+#[test]
+fn generate_puzzles_by_difficulty() {
+    for (difficulty, seed) in [(Difficulty::Easy, 5), (Difficulty::Medium, 5), (Difficulty::Hard, 5), (Difficulty::Expert, 7)] {
+        let puzzle = SudokuGrid::generate_with_rng(difficulty, &mut StdRng::seed_from_u64(seed));
+        let clues = puzzle.data.iter().filter(|&&v| v != 0).count();
+        assert!(difficulty.clue_band().contains(&clues), "A {} puzzle shouldn't have {} clues.", difficulty, clues);
+        assert_eq!(count_solutions(&puzzle, 2), 1, "The {} puzzle should be uniquely solvable.", difficulty);
+        assert_eq!(grade(&puzzle), difficulty)
+    }
+}
+
+
+#[test]
+fn from_data_rejects_malformed_data() {
+    assert!(SudokuGrid::from_data(&[0; 80]).is_err(), "A grid should hold exactly 81 values.");
+    assert!(SudokuGrid::from_data(&[0; 82]).is_err(), "A grid should hold exactly 81 values.");
+
+    let mut data = SudokuGrid::example_grid().data;
+    data[10] = 10;
+    assert_eq!(SudokuGrid::from_data(&data).err().as_deref(), Some("the value 10 at R2C2 isn't between 0 and 9."));
+    assert!(parse_grid_data(&data.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(",")).is_none(), "Out of range values should make the parsing fail.")
+}
+
+
+#[test]
+fn json_round_trip() {
+    let grid = SudokuGrid::example_grid();
+    let json = grid.to_json();
+    assert!(json.starts_with("[[5,3,0,0,7,0,0,0,0],[6,0,0,"), "Grids should be serialized as rows: {}", json);
+    assert_eq!(SudokuGrid::from_json(&json).map(|parsed| parsed.data), Ok(grid.data), "The grid should survive a round trip through JSON.");
+
+    assert_eq!(SudokuGrid::from_json("[[0,0,0,0,0,0,0,0,0]]").err().as_deref(), Some("a grid holds 9 rows, got 1."));
+    let short_row = json.replacen("[6,0,0,", "[6,0,", 1);
+    assert_eq!(SudokuGrid::from_json(&short_row).err().as_deref(), Some("a row holds 9 values, got 8 in row 2."));
+    assert!(SudokuGrid::from_json(&json.replacen('5', "12", 1)).is_err(), "Values above 9 should be rejected.")
+}
+
+
+#[test]
+fn solve_hexadoku() {
+    // Shifted pattern of a valid 16x16 grid, with more than half of the cells emptied
+    let puzzle = (0..256)
+        .map(|i| {
+            let (x, y) = (i % 16, i / 16);
+            if (x * 7 + y * 3) % 5 < 3 { 0 } else { ((4 * (y % 4) + y / 4 + x) % 16) as u8 + 1 }
+        })
+        .collect::<Vec<u8>>();
+    let grid = SudokuGrid::from_data_with_size(16, &puzzle).unwrap();
+    assert_eq!((grid.size(), grid.box_size()), (16, 4));
+
+    let line = grid.to_line_string();
+    assert!(line.starts_with("..2.4..7.9..C.E."), "Hexadoku digits should go from 0 to F: {}", line);
+    assert!(parse_sized_grid_info(&line, 16).is_ok_and(|parsed| parsed.data == grid.data), "The grid should survive a round trip through the line format.");
+
+    assert!(grid.candidates_mask().is_none() && grid.to_pencilmark_string().is_none(), "The pencil-mark views only handle 9x9 grids.");
+
+    let solved = solve(grid, MAX_ITERATIONS_DEFAULT).unwrap_or_else(|err| panic!("Couldn't solve the hexadoku: {}", err));
+    assert!(solved.is_solved(), "Every unit should hold the 16 digits.");
+    assert!(puzzle.iter().zip(solved.data.iter()).all(|(&clue, &value)| clue == 0 || clue == value), "The clues should be kept.");
+
+    assert!(SudokuGrid::from_data_with_size(16, &[0; 81]).is_err(), "A hexadoku holds 256 values.");
+    assert!(SudokuGrid::from_data_with_size(12, &[0; 144]).is_err(), "The size of a grid must be a square.");
+    assert!(SudokuGrid::from_data_with_size(4, &[0; 16]).is_err(), "Only 9x9 grids and hexadokus are supported.")
+}
+
+
+#[test]
+fn colored_string_tells_clues_from_solved_digits() {
+    let puzzle = SudokuGrid::example_grid();
+    let solved = solve(puzzle.clone(), MAX_ITERATIONS_DEFAULT).unwrap_or_else(|err| panic!("Couldn't solve the example grid: {}", err));
+    let colored = solved.to_colored_string(&puzzle);
+
+    assert!(colored.starts_with("\n|-----------------|\n| \x1b[1m5\x1b[0m\x1b[1m3\x1b[0m\x1b[32m4\x1b[0m | "), "Clues should be bold and solved digits green: {:?}", colored);
+    let stripped = colored.replace("\x1b[1m", "").replace("\x1b[32m", "").replace("\x1b[0m", "");
+    assert_eq!(stripped, solved.to_string(), "Without the escape codes, the layout should be the one of the Display implementation.")
+}
+
+
+#[test]
+fn hint_names_the_next_logical_move() {
+    let puzzle = SudokuGrid::example_grid();
+    let solution = solve(puzzle.clone(), MAX_ITERATIONS_DEFAULT).unwrap_or_else(|err| panic!("Couldn't solve the example grid: {}", err));
+
+    let hint = puzzle.hint().unwrap_or_else(|| panic!("The example grid can be solved with singles."));
+    assert_eq!(hint.value, solution.get(hint.x, hint.y), "The hint should agree with the solution: {}", hint);
+    assert_eq!(hint.technique, Technique::NakedSingle, "Naked singles should come first.");
+
+    // Following the hints solves the whole grid
+    let mut grid = puzzle;
+    while let Some(hint) = grid.hint() {
+        grid.set(hint.x, hint.y, hint.value)
+    }
+    assert_eq!(grid.diff(&solution), vec![], "Singles should be enough to solve the example grid.");
+
+    // Without naked singles, the hidden single of the row is found
+    let mut row = SudokuGrid::empty();
+    for x in 1..7 {
+        row.set(x, 0, x as u8 + 1)
+    }
+    row.set(7, 3, 1);
+    row.set(8, 6, 1);
+    assert_eq!(row.hint(), Some(Hint { x: 0, y: 0, value: 1, technique: Technique::HiddenSingle(UnitKind::Row) }));
+
+    assert!(SudokuGrid::empty().hint().is_none(), "An empty grid needs guessing.")
+}
+
+
+#[test]
+fn naked_subsets_help_propagation() {
+    // Two cells of the first row with the candidates 1 and 2 only: a naked pair
+    let mut candidates = [[0b1_1111_1111u16; 9]; 9];
+    candidates[0][0] = 0b11;
+    candidates[0][4] = 0b11;
+    assert!(eliminate_naked_subsets(&mut candidates, 2), "The pair should eliminate candidates.");
+    assert_eq!(candidates[0][8], 0b1_1111_1100, "1 and 2 should be removed from the rest of the row.");
+    assert_eq!(candidates[5][0], 0b1_1111_1111, "Other units shouldn't be affected.");
+    assert!(!eliminate_naked_subsets(&mut candidates, 2), "Eliminations should stop once the pair is isolated.");
+
+    // Naked triples don't need every cell to hold the three candidates
+    let mut candidates = [[0b1_1111_1111u16; 9]; 9];
+    candidates[3][0] = 0b011;
+    candidates[3][1] = 0b110;
+    candidates[3][2] = 0b101;
+    assert!(eliminate_naked_subsets(&mut candidates, 3));
+    assert_eq!(candidates[3][3], 0b1_1111_1000, "1, 2 and 3 should be removed from the rest of the row.");
+    assert_eq!(candidates[4][0], 0b1_1111_1000, "1, 2 and 3 should be removed from the rest of the group.");
+
+    // Singles stall on this puzzle, but naked subsets get it solved without guessing
+    let puzzle = parse_line_grid("85..7........59.13...1.......3....7......1...2...6....7....3..54......2.562...7..").unwrap();
+    let mut singles_only = puzzle.clone();
+    assert!(propagate_singles(&mut singles_only).is_ok() && !singles_only.is_complete(), "Singles alone shouldn't be enough.");
+    let solution = solve_logical_only(&puzzle).unwrap_or_else(|| panic!("Naked subsets should complete the puzzle."));
+    assert!(solution.is_solved(), "The deductions should lead to a valid solution.")
+}
+
+
+#[test]
+fn grade_by_hardest_technique() {
+    assert_eq!(grade(&SudokuGrid::example_grid()), Difficulty::Easy, "The example grid only needs singles.");
+    let pairs = parse_line_grid("85..7........59.13...1.......3....7......1...2...6....7....3..54......2.562...7..").unwrap();
+    assert_eq!(grade(&pairs), Difficulty::Medium, "This puzzle needs naked pairs.");
+    assert_eq!(grade(&SudokuGrid::builtin_puzzle("escargot").unwrap()), Difficulty::Expert, "The escargot puzzle needs guessing.")
+}
+
+
+#[test]
+fn svg_of_example_grid() {
+    let svg = SudokuGrid::example_grid().to_svg();
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"460\" height=\"460\""), "The document should be an SVG image: {}", svg);
+    assert!(svg.trim_end().ends_with("</svg>"));
+
+    // 10 vertical and 10 horizontal lines, the 4 of each on the group boundaries being thicker
+    assert_eq!(svg.matches("<line").count(), 20);
+    assert_eq!(svg.matches("stroke-width=\"3\"").count(), 8, "Group boundaries should be thick.");
+    assert!(svg.contains("<line x1=\"155\" y1=\"5\" x2=\"155\" y2=\"455\" stroke=\"black\" stroke-width=\"3\"/>"), "The line after the third column should be thick.");
+
+    // Only the 30 clues are drawn, the first one centered in the top left cell
+    assert_eq!(svg.matches("<text").count(), 30, "Empty cells should be blank.");
+    assert!(svg.contains("<text x=\"30\" y=\"30\" font-family=\"sans-serif\" font-size=\"32\" text-anchor=\"middle\" dominant-baseline=\"central\">5</text>"))
+}
+
+
+#[test]
+fn solve_x_sudoku() {
+    // This puzzle has several solutions under the classic rules, but a single one once the diagonals hold every digit
+    let mut puzzle = parse_line_grid(".2....78...6..9...7........9.5.4...7...5.....8......31.........3...6........72...").unwrap();
+    assert_eq!(count_solutions(&puzzle, 2), 2, "The puzzle shouldn't be unique as a classic sudoku.");
+
+    puzzle.set_kind(GridKind::Diagonal);
+    assert_eq!(count_solutions(&puzzle, 2), 1, "The puzzle should be unique as an X-Sudoku.");
+    let solved = solve(puzzle.clone(), MAX_ITERATIONS_DEFAULT).unwrap_or_else(|err| panic!("Couldn't solve the X-Sudoku: {}", err));
+    assert!(solved.is_solved(), "The diagonals of the solution should hold every digit.");
+    assert!(is_complete_unit(&solved.diagonal(false)) && is_complete_unit(&solved.diagonal(true)));
+
+    // A 4 in the top left corner rules out a 4 in the bottom right corner, but only in an X-Sudoku
+    puzzle.set(0, 0, 4);
+    assert!(!puzzle.check(8, 8, 4), "The main diagonal should already hold a 4.");
+    puzzle.set(8, 8, 4);
+    assert!(!puzzle.check_grid(), "Repeated digits on a diagonal should be rejected.");
+    puzzle.set_kind(GridKind::Classic);
+    assert!(puzzle.check_grid(), "Diagonals don't matter under the classic rules.")
+}
+
+
+#[test]
+fn sdk_format_round_trip() {
+    let grid = SudokuGrid::example_grid();
+    let sdk = grid.to_sdk_string();
+    assert_eq!(sdk.lines().next(), Some("53..7...."));
+    assert_eq!(sdk.lines().count(), 9);
+    assert!(parse_grid_info(&sdk).is_ok_and(|parsed| parsed.data == grid.data), "The grid should survive a round trip through the SDK format.");
+
+    // Comments, blank lines and spaces are skipped
+    let commented = format!("# Example grid\n#From the tests\n\n{}", sdk.replacen("53..7....", "5 3 . . 7 . . . .", 1));
+    assert!(parse_sdk_grid(&commented).is_some_and(|parsed| parsed.data == grid.data), "Comments should be skipped.");
+
+    let path = std::env::temp_dir().join("sudoku_solver_sdk_format_round_trip.sdk");
+    std::fs::write(&path, &commented).unwrap();
+    let from_file = parse_grid_info(path.to_str().unwrap());
+    std::fs::remove_file(&path).ok();
+    assert!(from_file.is_ok_and(|parsed| parsed.data == grid.data), "SDK files should be read.");
+
+    assert!(parse_sdk_grid(&sdk.replacen("53..7....\n", "", 1)).is_none(), "8 lines don't make a grid.")
+}
+
+
+#[test]
+fn iterate_over_solutions() {
+    let example = SudokuGrid::example_grid();
+    let solutions: Vec<SudokuGrid> = example.clone().solutions().collect();
+    assert_eq!(solutions.len(), 1, "The example grid has a single solution.");
+    assert_eq!(Some(solutions[0].data.clone()), solve(example, MAX_ITERATIONS_DEFAULT).ok().map(|solved| solved.data));
+
+    // Removing a few clues of the example grid leaves it with several solutions, all different
+    let mut ambiguous = SudokuGrid::example_grid();
+    for (x, y) in [(0, 0), (1, 0), (4, 0), (0, 1), (3, 1), (4, 1)] {
+        ambiguous.set(x, y, 0)
+    }
+    let solutions: Vec<SudokuGrid> = ambiguous.clone().solutions().collect();
+    assert_eq!(solutions.len(), 64);
+    assert!(solutions.iter().all(|solution| solution.is_solved()));
+    assert!(solutions.iter().enumerate().all(|(i, a)| solutions[i + 1..].iter().all(|b| a.data != b.data)), "Every solution should be yielded once.");
+
+    // The empty grid is fine as long as the iteration is bounded
+    let first = SudokuGrid::empty().solutions().take(3).collect::<Vec<SudokuGrid>>();
+    assert_eq!(first.len(), 3);
+    assert!(first.iter().all(|solution| solution.is_solved()));
+
+    let mut invalid = SudokuGrid::example_grid();
+    invalid.set(2, 0, 5);
+    assert_eq!(invalid.solutions().count(), 0, "An invalid grid has no solution.")
+}
+
+
+#[test]
+fn invalid_grid_reports_the_conflict() {
+    let mut grid = SudokuGrid::example_grid();
+    grid.set(2, 0, 5);
+    assert_eq!(grid.validate(), Err(Conflict { kind: UnitKind::Row, x: 0, y: 0, value: 5 }));
+
+    let mut grid = SudokuGrid::example_grid();
+    grid.set(2, 4, 8);
+    assert_eq!(grid.validate(), Err(Conflict { kind: UnitKind::Column, x: 2, y: 2, value: 8 }));
+    match solve(grid, MAX_ITERATIONS_DEFAULT) {
+        Err(err @ SudokuSolvingError::InvalidGrid(Some(_))) => assert_eq!(err.to_string(), "The supplied sudoku grid is invalid: digit 8 appears twice in column 3!"),
+        _ => panic!("The conflict should be reported.")
+    }
+
+    let mut grid = SudokuGrid::example_grid();
+    grid.set(1, 1, 8);
+    assert_eq!(grid.validate().map_err(|conflict| conflict.kind), Err(UnitKind::Group));
+    assert!(SudokuGrid::example_grid().validate().is_ok());
+    assert!(matches!(solve(SudokuGrid::empty(), MAX_ITERATIONS_DEFAULT), Err(SudokuSolvingError::InvalidGrid(None))), "An empty grid has no conflict to report.")
+}
+
+
+#[test]
+fn check_grid_covers_last_row_and_column() {
+    // A second 9 in the last row, clashing with R9C9 only
+    let mut grid = SudokuGrid::example_grid();
+    grid.set(0, 8, 9);
+    assert!(!grid.check_grid(), "A repeated digit in the last row should be detected.");
+    assert_eq!(grid.validate().map_err(|conflict| conflict.kind), Err(UnitKind::Row));
+
+    // A second 1 in the last column, clashing with R5C9 only
+    let mut grid = SudokuGrid::example_grid();
+    grid.set(8, 0, 1);
+    assert!(!grid.check_grid(), "A repeated digit in the last column should be detected.");
+    assert_eq!(grid.validate(), Err(Conflict { kind: UnitKind::Column, x: 8, y: 0, value: 1 }));
+    assert!(matches!(solve(grid, MAX_ITERATIONS_DEFAULT), Err(SudokuSolvingError::InvalidGrid(Some(_)))), "The solver should reject the grid upfront.")
+}
+
+
+#[test]
+fn timeout_stops_the_search() {
+    let puzzle = parse_line_grid("..............3.85..1.2.......5.7.....4...1...9.......5......73..2.1........4...9").unwrap();
+    let timed_out = SolveOptions::new(u32::MAX).propagate(false).timeout(Duration::ZERO);
+    assert!(matches!(solve_with_options(puzzle.clone(), &timed_out), Err(SudokuSolvingError::Timeout)), "The search should give up once the deadline has passed.");
+    assert_eq!(SudokuSolvingError::Timeout.marker(), "TIMEOUT");
+
+    let generous = SolveOptions::default().timeout(Duration::from_secs(60));
+    assert!(solve_with_options(SudokuGrid::example_grid(), &generous).is_ok(), "A grid solved in time should be unaffected by the timeout.")
+}
+
+
+#[test]
+fn intersections_remove_candidates() {
+    let mut candidates = [[0x1FF; 9]; 9];
+    // Pointing pair: in the top left group, 1 can only go in the first row
+    for (x, y) in [(0, 1), (1, 1), (2, 1), (0, 2), (1, 2), (2, 2)] {
+        candidates[y][x] &= !1
+    }
+    // Box-line reduction: in the fifth row, 2 can only go in the central group
+    for x in [0, 1, 2, 6, 7, 8] {
+        candidates[4][x] &= !2
+    }
+
+    assert!(eliminate_intersections(&mut candidates), "Candidates should be removed.");
+    assert!((3..9).all(|x| candidates[0][x] & 1 == 0), "1 should be removed from the rest of the first row.");
+    assert!((3..6).all(|x| candidates[3][x] & 2 == 0 && candidates[5][x] & 2 == 0), "2 should be removed from the rest of the central group.");
+    assert_eq!(candidates[0][0], 0x1FF);
+    assert_eq!(candidates[4][4], 0x1FF);
+
+    // This puzzle stalls with singles and naked subsets alone
+    let puzzle = parse_line_grid("3.1..8..78.7......4.6..192...9...45.64..2.17...2.7......3.4..9..8..62.1......9..4").unwrap();
+    let mut without_intersections = puzzle.clone();
+    assert!(propagate_with_techniques(&mut without_intersections, false, 3).is_ok_and(|_| !without_intersections.is_complete()), "Naked subsets alone shouldn't solve the puzzle.");
+
+    let mut solved = puzzle.clone();
+    assert!(propagate(&mut solved).is_ok_and(|_| solved.is_complete()), "Intersections should solve the puzzle.");
+    assert!(solve_dlx(puzzle.clone()).is_ok_and(|expected| expected.data == solved.data), "The solution should be the unique one.");
+    assert_eq!(grade(&puzzle), Difficulty::Medium)
+}
+
+
+#[test]
+fn parse_grid_accepts_any_layout() {
+    let grid = SudokuGrid::example_grid();
+    let line = grid.to_line_string();
+
+    let spaced = line.chars().enumerate().map(|(i, c)| if i % 9 == 8 { format!("{}\n", c) } else { format!("{} ", c) }).collect::<String>();
+    let markers = line.replacen('.', "0", 5).replacen('.', "_", 5).replacen('.', "*", 5).replacen('.', "x", 5);
+    let commas = line.chars().map(String::from).collect::<Vec<_>>().join(",");
+    for data in [&line, &spaced, &markers, &commas] {
+        assert!(parse_grid(data).is_ok_and(|parsed| parsed.data == grid.data), "The grid should be parsed from {:?}.", data)
+    }
+
+    assert_eq!(parse_grid(&line[1..]).err(), Some(ParseError::CellCount { found: 80, expected: 81 }));
+    assert_eq!(parse_grid(&line.replacen('.', "q", 1)).map_err(|err| err.to_string()).err().as_deref(), Some("invalid char 'q' at position 3"));
+
+    // Files go through the same parsing
+    let path = std::env::temp_dir().join("sudoku_solver_parse_grid_accepts_any_layout.txt");
+    std::fs::write(&path, &markers).unwrap();
+    let from_file = parse_grid_info(path.to_str().unwrap());
+    std::fs::remove_file(&path).ok();
+    assert!(from_file.is_ok_and(|parsed| parsed.data == grid.data), "The file should be parsed like direct data.")
+}
+
+
+/// Cages of a Killer Sudoku without any given digit, whose solution is the one of the example grid.
+const KILLER_CAGES: &str = "# Killer Sudoku with a unique solution
+21 R1C1 R2C1 R3C1 R3C2
+15 R1C2 R1C3 R1C4 R2C3
+15 R1C5 R1C6
+12 R1C7 R2C7
+11 R1C8 R1C9 R2C9
+7 R2C2
+10 R2C4 R2C5
+7 R2C6 R3C6
+17 R2C8 R3C8 R3C9
+20 R3C3 R3C4 R4C3
+10 R3C5 R4C5
+9 R3C7 R4C7
+13 R4C1 R4C2
+15 R4C4 R5C4
+4 R4C6 R5C6
+12 R4C8 R5C8 R5C9
+3 R4C9
+6 R5C1 R5C2
+9 R5C3 R6C3
+10 R5C5 R6C5 R7C5
+20 R5C7 R6C7 R6C8
+14 R6C1 R6C2 R7C2
+15 R6C4 R7C4 R7C3
+11 R6C6 R7C6
+18 R6C9 R7C9 R7C8
+14 R7C1 R8C1 R9C1
+11 R7C7 R8C7 R8C8
+20 R8C2 R8C3 R9C3
+7 R8C4 R9C4 R8C5
+24 R8C6 R9C6 R9C5 R9C7
+21 R8C9 R9C9 R9C8
+4 R9C2
+";
+
+#[test]
+fn solve_killer_sudoku() {
+    let cages = parse_cages(KILLER_CAGES).unwrap_or_else(|| panic!("The cages should be parsed."));
+    assert_eq!(cages.len(), 32);
+    assert_eq!(cages[0], Cage { cells: vec![(0, 0), (0, 1), (0, 2), (1, 2)], sum: 21 });
+
+    let mut puzzle = SudokuGrid::empty();
+    puzzle.set_cages(cages);
+    assert!(puzzle.check_grid(), "The cages are the clues of an empty grid.");
+    assert!(puzzle.check(4, 0, 6) && !puzzle.check(4, 0, 1), "With 1, the other cell of the cage adding up to 15 would need 14.");
+
+    let solved = solve(puzzle.clone(), MAX_ITERATIONS_DEFAULT).unwrap_or_else(|err| panic!("Couldn't solve the killer sudoku: {}", err));
+    assert!(solved.is_solved(), "The cages should add up.");
+    assert_eq!(solved.diff(&solve(SudokuGrid::example_grid(), MAX_ITERATIONS_DEFAULT).ok().unwrap()), vec![]);
+    assert!(solve_dlx(puzzle.clone()).is_err(), "Dancing links only know the classic rules.");
+
+    // A cage can't repeat a digit
+    let mut repeated = puzzle.clone();
+    repeated.set(3, 0, 5);
+    repeated.set(2, 1, 5);
+    assert_eq!(repeated.validate(), Err(Conflict { kind: UnitKind::Cage, x: 3, y: 0, value: 5 }));
+    let mut over = puzzle;
+    over.set(4, 0, 9);
+    over.set(5, 0, 8);
+    assert!(!over.check_grid(), "9 and 8 go over the sum of 15.");
+
+    assert!(parse_cages("10 R1C1 R1C2\n5 R1C2").is_none(), "A cell can't belong to two cages.");
+    assert!(parse_cages("50 R1C1 R1C2").is_none(), "A cage adds up to at most 45.")
+}
+
+
+#[test]
+fn unicode_drawing_aligns() {
+    let drawing = SudokuGrid::example_grid().to_unicode_string();
+    let lines: Vec<&str> = drawing.lines().skip(1).collect();
+    assert_eq!(lines.len(), 19, "Every row should have a line above it, and the last one below it.");
+    assert!(lines.iter().all(|line| line.chars().count() == 37), "Every line should have the same width.");
+
+    assert_eq!(lines[0], "┏━━━┯━━━┯━━━┳━━━┯━━━┯━━━┳━━━┯━━━┯━━━┓");
+    assert_eq!(lines[1], "┃ 5 │ 3 │   ┃   │ 7 │   ┃   │   │   ┃", "Empty cells should be blank.");
+    assert_eq!(lines[6], "┣━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━┫", "Groups should be separated by heavy lines.");
+    assert_eq!(lines[18], "┗━━━┷━━━┷━━━┻━━━┷━━━┷━━━┻━━━┷━━━┷━━━┛")
+}
+
+
+#[test]
+fn candidates_of_a_constrained_cell() {
+    let mut grid = SudokuGrid::empty();
+    // 1 to 3 in the row, 4 to 6 in the column and 7 and 8 in the group of the central cell
+    for (x, y, value) in [(0, 4, 1), (1, 4, 2), (8, 4, 3), (4, 0, 4), (4, 1, 5), (4, 8, 6), (3, 3, 7), (5, 5, 8)] {
+        grid.set(x, y, value)
+    }
+    assert_eq!(grid.candidates(4, 4), vec![9]);
+    assert_eq!(grid.candidates_mask().unwrap()[4][4], 1 << 8, "Bit 8 stands for the digit 9.");
+    assert!(grid.candidates(0, 4).is_empty() && grid.candidates_mask().unwrap()[4][0] == 0, "A filled cell has no candidate.");
+
+    let example = SudokuGrid::example_grid();
+    let mask = example.candidates_mask().unwrap();
+    assert!((0..81).all(|i| mask[i / 9][i % 9] == example.candidates(i % 9, i / 9).iter().fold(0, |mask, &value| mask | 1 << (value - 1))), "Both methods should agree.")
+}
+
+
+#[test]
+fn valid_random_is_solved() {
+    let grid = SudokuGrid::valid_random();
+    assert!(grid.is_solved(), "The random grid should be complete and valid:{}", grid);
+    assert!(parse_grid_info("random").is_ok_and(|template| template.is_solved()), "The template should be a solved grid.")
+}
+
+
+#[test]
+fn explain_narrates_the_solving() {
+    let puzzle = parse_line_grid("3.1..8..78.7......4.6..192...9...45.64..2.17...2.7......3.4..9..8..62.1......9..4").unwrap();
+    let (steps, solved) = explain(&puzzle).unwrap_or_else(|err| panic!("Couldn't explain the puzzle: {}", err));
+    assert!(solve_dlx(puzzle.clone()).is_ok_and(|expected| expected.data == solved.data), "The explanation should end with the solution.");
+    assert!(!steps.iter().any(|step| matches!(step, Step::Guess { .. })), "The puzzle can be solved by logic alone.");
+    assert!(steps.iter().any(|step| matches!(step, Step::Eliminate { reason: Elimination::Pointing(_), .. })), "The puzzle needs intersections.");
+
+    // Replaying the placements solves the puzzle
+    let mut replayed = puzzle.clone();
+    for step in &steps {
+        if let Step::Place(hint) = step {
+            assert_eq!(solved.get(hint.x, hint.y), hint.value, "{} should be right.", step);
+            replayed.set(hint.x, hint.y, hint.value)
+        }
+    }
+    assert_eq!(replayed.diff(&solved), vec![]);
+
+    assert_eq!(steps[0].to_string(), "R3C2 = 5 (naked single)");
+    assert_eq!(Step::Eliminate { x: 1, y: 0, value: 4, reason: Elimination::NakedSubset(vec![(6, 0), (8, 0)]) }.to_string(), "R1C2 ≠ 4 (naked pair R1C7/R1C9)");
+    assert_eq!(Step::Guess { x: 3, y: 3, value: 2 }.to_string(), "guessing R4C4 = 2 (backtracking)");
+
+    let (steps, solved) = explain(&SudokuGrid::builtin_puzzle("escargot").unwrap()).unwrap_or_else(|err| panic!("Couldn't explain the puzzle: {}", err));
+    assert!(solved.is_solved(), "Guessing should finish the hard puzzle.");
+    assert!(steps.iter().any(|step| matches!(step, Step::Guess { .. })), "The hard puzzle needs guessing.")
+}
+
+
+#[test]
+fn solve_from_pencil_marks() {
+    let mut puzzle = SudokuGrid::example_grid();
+    let solution = solve(puzzle.clone(), MAX_ITERATIONS_DEFAULT).ok().unwrap();
+    puzzle.set(2, 0, 4);
+    let mut candidates = puzzle.candidates_mask().unwrap();
+    for (i, &value) in puzzle.data.iter().enumerate() {
+        if value != 0 {
+            candidates[i / 9][i % 9] = 1 << (value - 1)
+        }
+    }
+
+    let pencil_marks = candidates;
+
+    // A single candidate makes a placed digit
+    let grid = SudokuGrid::from_candidates(&candidates).unwrap_or_else(|err| panic!("The pencil marks should be consistent: {}", err));
+    assert_eq!(grid.get(2, 0), 4);
+    assert!(solve(grid.clone(), MAX_ITERATIONS_DEFAULT).is_ok_and(|solved| solved.data == solution.data), "The solution should respect the pencil marks.");
+
+    // Leaving out the right digit of a cell makes the grid unsolvable, since the pencil marks are respected
+    let value = solution.get(3, 0);
+    candidates[0][3] &= !(1 << (value - 1));
+    let wrong = SudokuGrid::from_candidates(&candidates).unwrap_or_else(|err| panic!("The pencil marks should be consistent: {}", err));
+    assert!(!wrong.check(3, 0, value), "The digit left out shouldn't be accepted.");
+    assert!(matches!(solve(wrong, MAX_ITERATIONS_DEFAULT), Err(SudokuSolvingError::Unsolvable)));
+
+    // 5 and 7 are placed in the first row
+    candidates[0][3] = 1 << 4 | 1 << 6;
+    assert!(SudokuGrid::from_candidates(&candidates).is_err_and(|err| err.starts_with("R1C4 only lists")), "A cell whose candidates are all placed in its units should be rejected.");
+    candidates[0][3] = 0;
+    assert!(SudokuGrid::from_candidates(&candidates).is_err(), "A cell without candidates should be rejected.");
+
+    let marks = pencil_marks.iter().flatten().map(|&mask| (1..=9).filter(|digit| mask & 1 << (digit - 1) != 0).map(|digit| digit.to_string()).collect::<String>()).collect::<Vec<_>>().join(" ");
+    assert!(parse_grid_info(&marks).is_ok_and(|parsed| parsed.data == grid.data && parsed.candidates_mask().unwrap() == grid.candidates_mask().unwrap()), "The pencil marks should be parsed back.")
+}
+
+
+#[test]
+fn debug_is_a_single_line() {
+    let grid = SudokuGrid::example_grid();
+    let debug = format!("{:?}", grid);
+    assert_eq!(debug.chars().count(), 81);
+    assert!(!debug.contains('\n'), "The compact form should fit in a log line.");
+    assert!(parse_grid(&debug).is_ok_and(|parsed| parsed.data == grid.data), "The compact form should be parsed back.");
+
+    assert_eq!(format!("{:?}", SudokuGrid::empty()), ".".repeat(81));
+    let solved = solve(grid, MAX_ITERATIONS_DEFAULT).ok().unwrap();
+    assert_eq!(format!("{:?}", solved).chars().count(), 81);
+    assert!(format!("{}", solved).lines().count() > 1, "Display should stay multi-line.")
+}
+
+
+#[cfg(feature = "rayon")]
+#[test]
+fn parallel_count_matches_sequential_count() {
+    // The example grid without a few clues has 64 solutions
+    let mut ambiguous = SudokuGrid::example_grid();
+    for (x, y) in [(0, 0), (1, 0), (4, 0), (0, 1), (3, 1), (4, 1)] {
+        ambiguous.set(x, y, 0)
+    }
+
+    for limit in [1, 2, 10, 64, 1000] {
+        assert_eq!(count_solutions_parallel(&ambiguous, limit), count_solutions(&ambiguous, limit), "The counts should match with a limit of {}.", limit)
+    }
+    assert_eq!(count_solutions_parallel(&SudokuGrid::example_grid(), 2), 1);
+    assert_eq!(count_solutions_parallel(&solve(SudokuGrid::example_grid(), MAX_ITERATIONS_DEFAULT).ok().unwrap(), 2), 1, "A solved grid is its own solution.")
+}
+
+
+#[cfg(feature = "wasm")]
+#[test]
+fn wasm_entry_points_use_lines() {
+    let solution = wasm::solve_line(&SudokuGrid::example_grid().to_line_string());
+    assert!(parse_grid(&solution).is_ok_and(|grid| grid.is_solved()), "Unexpected solution: {}", solution);
+    assert!(wasm::solve_line("123").starts_with("error: found 3 cells"));
+
+    let puzzle = wasm::generate_line("easy");
+    assert_eq!(puzzle.len(), 81);
+    assert!(parse_grid(&puzzle).is_ok_and(|grid| count_solutions(&grid, 2) == 1), "The puzzle should have a unique solution.");
+    assert!(wasm::generate_line("impossible").starts_with("error:"))
+}
+
+
+#[test]
+fn deep_backtracking_finds_correct_solutions() {
+    let puzzles = [
+        SudokuGrid::builtin_puzzle("escargot").unwrap(),
+        SudokuGrid::builtin_puzzle("example2").unwrap(),
+        parse_line_grid("..............3.85..1.2.......5.7.....4...1...9.......5......73..2.1........4...9").unwrap()
+    ];
+    let backtracking_only = SolveOptions::default().propagate(false);
+
+    for puzzle in puzzles {
+        let mut stats = SolveStats::default();
+        let solved = solve_with_stats(puzzle.clone(), &backtracking_only, &mut stats).unwrap_or_else(|(err, _)| panic!("Couldn't solve {:?}: {}", puzzle, err));
+        assert!(stats.iterations > 100, "{:?} should need deep backtracking.", puzzle);
+        assert!(solved.is_solved(), "The solution of {:?} should follow every rule: {:?}", puzzle, solved);
+        assert!(puzzle.data.iter().zip(&solved.data).all(|(&given, &value)| given == 0 || given == value), "The givens of {:?} should be kept.", puzzle)
+    }
+}
+
+
+#[test]
+fn undo_and_redo_tracked_moves() {
+    let mut grid = SudokuGrid::example_grid();
+    grid.set_tracked(2, 0, 4);
+    grid.set_tracked(3, 0, 6);
+    grid.set_tracked(2, 0, 1);
+    assert!(grid.undo() && grid.undo(), "Two moves should be undone.");
+    assert_eq!((grid.get(2, 0), grid.get(3, 0)), (4, 0), "Only the first move should remain.");
+    assert!(grid.redo(), "The second move should be redone.");
+    assert_eq!((grid.get(2, 0), grid.get(3, 0)), (4, 6));
+
+    // A new move drops the move that is left to redo
+    grid.set_tracked(5, 0, 8);
+    assert!(!grid.redo(), "Nothing should be left to redo after a new move.");
+    assert!(grid.undo() && grid.undo() && grid.undo() && !grid.undo(), "Every move should be undone once.");
+    assert_eq!(grid.diff(&SudokuGrid::example_grid()), vec![], "Undoing every move should restore the grid.");
+    assert!(grid.check(2, 0, 4), "The digits of the undone moves should be allowed again.")
+}
+
+
+#[test]
+fn solve_anti_knight_sudoku() {
+    let mut puzzle = parse_grid("....5...99..31.4...6..9.....5....6..734...........4.3251.84...7.7.....4.........5").unwrap();
+    assert!(count_solutions(&puzzle, 2) > 1, "The grid alone should be ambiguous.");
+    puzzle.set_kind(GridKind::AntiKnight);
+    assert_eq!(count_solutions(&puzzle, 2), 1, "The knight moves should make the solution unique.");
+    let solved = solve(puzzle, MAX_ITERATIONS_DEFAULT).unwrap_or_else(|err| panic!("Couldn't solve the anti-knight sudoku: {}", err));
+    assert!(solved.is_solved(), "No digit of the solution should be a knight's move away from itself.");
+    assert_eq!(solved.to_line_string(), "123456789987312456465798213251983674734621598896574132512849367378265941649137825");
+
+    assert_eq!(solved.knight_neighbors(0, 0), vec![(2, 1), (1, 2)], "Moves off the grid should be skipped.");
+    assert_eq!(solved.knight_neighbors(4, 4).len(), 8);
+    let mut broken = SudokuGrid::empty();
+    broken.set_kind(GridKind::AntiKnight);
+    broken.set(4, 4, 5);
+    assert!(!broken.check(6, 5, 5) && broken.check(6, 6, 5), "Only the cells a knight's move away should be ruled out.");
+    broken.set(6, 5, 5);
+    assert_eq!(broken.validate().map_err(|conflict| conflict.to_string()), Err(String::from("digit 5 appears a knight's move away from R5C5")))
+}
+
+
+#[test]
+fn csv_round_trip() {
+    let grid = SudokuGrid::example_grid();
+    let csv = grid.to_csv();
+    assert_eq!(csv.lines().next(), Some("5,3,,,7,,,,"));
+    assert!(SudokuGrid::from_csv(&csv).is_ok_and(|parsed| parsed.data == grid.data), "The CSV output should be parsed back.");
+    assert!(parse_grid_info(&csv).is_ok_and(|parsed| parsed.data == grid.data), "Grid info of 9 comma-separated rows should be read as CSV.");
+
+    // Spreadsheets may quote the fields and leave a trailing line
+    let quoted = csv.replacen("5,3,", "\"5\",\" 3 \",", 1) + "\n";
+    assert!(SudokuGrid::from_csv(&quoted).is_ok_and(|parsed| parsed.data == grid.data), "Quoted fields should be accepted.");
+    assert!(SudokuGrid::from_csv(&csv.replacen("5,3,", "5,3", 1)).is_err(), "A row of 8 cells should be rejected.");
+    assert!(SudokuGrid::from_csv(&csv.lines().skip(1).collect::<Vec<&str>>().join("\n")).is_err(), "8 rows should be rejected.");
+    assert_eq!(parse_grid_info(&csv.replacen('5', "x", 1)).err(), Some(ParseError::InvalidCsv(String::from("the cell 'x' at R1C1 isn't a digit."))))
+}
+
+
+#[test]
+fn empty_cells_and_counts() {
+    let grid = SudokuGrid::example_grid();
+    assert_eq!(grid.filled_count(), 30);
+    assert_eq!(grid.empty_count(), 51);
+    assert_eq!(grid.empty_cells().len(), grid.empty_count(), "Every empty cell should be listed.");
+    assert_eq!(grid.empty_cells()[..3], [(2, 0), (3, 0), (5, 0)], "The empty cells should be listed in row-major order.");
+    assert_eq!(SudokuGrid::empty().empty_count(), 81)
+}
+
+
+#[test]
+fn same_seed_same_grid() {
+    use rand::rngs::StdRng;
+    let grid = |seed| SudokuGrid::random_solution(&mut StdRng::seed_from_u64(seed));
+    assert_eq!(grid(42).diff(&grid(42)), vec![], "The same seed should give the same solution.");
+    assert_ne!(grid(42).data, grid(43).data, "Different seeds should give different solutions.");
+    let puzzle = |seed| SudokuGrid::generate_with_rng(Difficulty::Easy, &mut StdRng::seed_from_u64(seed));
+    assert_eq!(puzzle(7).diff(&puzzle(7)), vec![], "The same seed should give the same puzzle.");
+    assert_eq!(SudokuGrid::randomly_filled_with_rng(&mut StdRng::seed_from_u64(1)).data, SudokuGrid::randomly_filled_with_rng(&mut StdRng::seed_from_u64(1)).data)
+}
+
+
+#[test]
+fn rotationally_symmetric_generation() {
+    use rand::rngs::StdRng;
+    let puzzle = SudokuGrid::generate_symmetric(Difficulty::Medium, Symmetry::Rotational180, &mut StdRng::seed_from_u64(3));
+    assert!((0..81).all(|i| (puzzle.data[i] == 0) == (puzzle.data[80 - i] == 0)), "The clues should be the same after a half turn: {:?}", puzzle);
+    assert_eq!(count_solutions(&puzzle, 2), 1, "The symmetric puzzle should stay unique.");
+    assert_eq!(Symmetry::Rotational180.orbit(40), vec![40], "The center cell is its own image.");
+    assert_eq!(Symmetry::Horizontal.orbit(1), vec![1, 73]);
+    assert_eq!(Symmetry::Diagonal.orbit(1), vec![1, 9]);
+
+    // With a single attempt, the same generation gives up and hands back the puzzle it ended up with
+    let bounded = SudokuGrid::try_generate_symmetric(Difficulty::Medium, Symmetry::Rotational180, &mut StdRng::seed_from_u64(3), 1);
+    assert!(matches!(bounded, Err(GenError::TargetNotMet { best }) if best.filled_count() > 0), "The first attempt shouldn't meet the target.");
+    assert!(SudokuGrid::try_generate_symmetric(Difficulty::Easy, Symmetry::Rotational180, &mut StdRng::seed_from_u64(3), GENERATION_ATTEMPTS_DEFAULT).is_ok(), "An easy puzzle comes quickly.")
+}
+
+
+#[test]
+fn verify_a_proposed_solution() {
+    let puzzle = SudokuGrid::example_grid();
+    let solution = solve(puzzle.clone(), MAX_ITERATIONS_DEFAULT).unwrap_or_else(|err| panic!("Couldn't solve the example grid: {}", err));
+    assert!(solution.is_solution_of(&puzzle), "The solution should solve the puzzle.");
+    assert!(!solution.is_solution_of(&SudokuGrid::builtin_puzzle("easy1").unwrap()), "The solution shouldn't match the clues of another puzzle.");
+
+    let mut incomplete = solution.clone();
+    incomplete.set(2, 0, 0);
+    assert!(!incomplete.is_solution_of(&puzzle), "An incomplete grid isn't a solution.");
+
+    // Several solutions can be right when the puzzle isn't unique
+    let mut loose = puzzle.clone();
+    loose.set(0, 0, 0);
+    loose.set(1, 0, 0);
+    assert!(solution.is_solution_of(&loose), "Any completion of the clues should be accepted.")
+}
+
+
+/// Regions of the Jigsaw Sudoku of `solve_jigsaw_sudoku()`, a character per region.
+const JIGSAW_REGIONS: &str = "\
+112223333
+111122233
+141222633
+144555636
+445555666
+444455666
+777799999
+787779889
+788888899
+";
+
+#[test]
+fn solve_jigsaw_sudoku() {
+    let regions = parse_regions(JIGSAW_REGIONS).unwrap_or_else(|| panic!("The regions should be parsed."));
+    assert_eq!((regions[0][0], regions[2][1], regions[8][8]), (0, 3, 7), "Regions should be numbered in order of appearance, 9 coming before 8.");
+    let mut puzzle = parse_grid("...4.6..97..561.3......7....52..8.....56..1.24..........61.587.2...3456.5........").unwrap();
+    assert!(!puzzle.check_grid(), "The clues should break the square groups.");
+    puzzle.set_regions(Some(regions)).unwrap_or_else(|err| panic!("The regions should be accepted: {}", err));
+    assert!(puzzle.check_grid(), "The clues should follow the regions.");
+    assert_eq!(count_solutions(&puzzle, 2), 1, "The jigsaw puzzle should have a unique solution.");
+
+    let solved = solve(puzzle, MAX_ITERATIONS_DEFAULT).unwrap_or_else(|err| panic!("Couldn't solve the jigsaw sudoku: {}", err));
+    assert!(solved.is_solved(), "Every region of the solution should hold each digit.");
+    assert_eq!(solved.to_line_string(), "123456789789561234314897625652348917835679142467912358946125873298734561571283496");
+    assert_eq!(solved.group(1, 2), vec![1, 5, 2, 8, 3, 4, 6, 7, 9], "The group of a cell should be its region.");
+
+    let mut uneven = regions;
+    uneven[0][0] = 1;
+    assert!(SudokuGrid::empty().set_regions(Some(uneven)).is_err(), "Regions of other sizes than 9 cells should be rejected.")
+}
+
+
+#[test]
+fn stats_count_the_backtracking() {
+    let mut stats = SolveStats::default();
+    let options = SolveOptions::default().propagate(false);
+    let solved = solve_with_stats(SudokuGrid::builtin_puzzle("escargot").unwrap(), &options, &mut stats).unwrap_or_else(|(err, _)| panic!("Couldn't solve the escargot grid: {}", err));
+    assert!(solved.is_solved());
+    assert!(stats.backtracks > 0, "The escargot grid needs to step back from dead ends.");
+    assert!(stats.max_depth > 0 && stats.max_depth <= 81 - 23, "The depth is bounded by the empty cells: {}", stats.max_depth);
+    assert!(stats.elapsed > Duration::ZERO, "The elapsed time should always be measured.");
+    assert!(stats.to_string().starts_with(&format!("Iterations: {}, backtracks: {}", stats.iterations, stats.backtracks)));
+
+    // Singles alone solve the easy puzzle without any guess
+    solve_with_stats(SudokuGrid::builtin_puzzle("easy1").unwrap(), &SolveOptions::default(), &mut stats).unwrap_or_else(|(err, _)| panic!("Couldn't solve the easy grid: {}", err));
+    assert_eq!((stats.backtracks, stats.max_depth), (0, 0))
+}
+
+
+#[test]
+fn geometric_transforms_commute_with_solving() {
+    let puzzle = SudokuGrid::example_grid();
+    let solution = solve(puzzle.clone(), MAX_ITERATIONS_DEFAULT).unwrap_or_else(|err| panic!("Couldn't solve the example grid: {}", err));
+    type Transform = fn(&SudokuGrid) -> SudokuGrid;
+    let transforms: [(&str, Transform); 6] = [
+        ("rotate90", SudokuGrid::rotate90),
+        ("rotate180", SudokuGrid::rotate180),
+        ("rotate270", SudokuGrid::rotate270),
+        ("mirror_horizontal", SudokuGrid::mirror_horizontal),
+        ("mirror_vertical", SudokuGrid::mirror_vertical),
+        ("transpose", SudokuGrid::transpose)
+    ];
+
+    for (name, transform) in transforms {
+        let solved = solve(transform(&puzzle), MAX_ITERATIONS_DEFAULT).unwrap_or_else(|err| panic!("Couldn't solve the {} grid: {}", name, err));
+        assert_eq!(solved.diff(&transform(&solution)), vec![], "Solving then applying {} should match applying it then solving.", name);
+        assert!(transform(&solution).is_solved(), "{} should keep the solution valid.", name)
+    }
+
+    assert_eq!(puzzle.rotate90().get(8, 0), puzzle.get(0, 0), "The top left corner should go to the top right corner.");
+    assert_eq!(puzzle.rotate90().rotate90().data, puzzle.rotate180().data);
+    assert_eq!(puzzle.rotate90().rotate270().data, puzzle.data);
+    assert_eq!(puzzle.transpose().row(0), puzzle.column(0))
+}
+
+
+#[test]
+fn relabeling_commutes_with_solving() {
+    use rand::rngs::StdRng;
+    let puzzle = SudokuGrid::builtin_puzzle("example2").unwrap();
+    let solution = solve(puzzle.clone(), MAX_ITERATIONS_DEFAULT).unwrap_or_else(|err| panic!("Couldn't solve the example grid: {}", err));
+    let mapping = SudokuGrid::random_relabeling(&mut StdRng::seed_from_u64(9));
+    let relabeled = puzzle.relabel(mapping).unwrap_or_else(|err| panic!("The mapping should be a permutation: {}", err));
+    assert_eq!(relabeled.empty_cells(), puzzle.empty_cells(), "Empty cells should stay empty.");
+
+    let solved = solve(relabeled, MAX_ITERATIONS_DEFAULT).unwrap_or_else(|err| panic!("Couldn't solve the relabeled grid: {}", err));
+    assert_eq!(Some(solved.data), solution.relabel(mapping).ok().map(|grid| grid.data), "Relabeling then solving should match solving then relabeling.");
+
+    // Swapping the 1s and 9s is a permutation, repeating a digit isn't
+    assert!(solution.relabel([9, 2, 3, 4, 5, 6, 7, 8, 1]).is_ok_and(|grid| grid.is_solved()));
+    assert_eq!(solution.relabel([1, 1, 3, 4, 5, 6, 7, 8, 9]).err(), Some(String::from("the mapping isn't a permutation of the digits, 2 is missing.")))
+}
+
+
+#[test]
+fn read_gzipped_puzzle_files() {
+    use std::io::Write;
+
+    let example = SudokuGrid::example_grid();
+    let path = std::env::temp_dir().join("sudoku_solver_read_gzipped_puzzle_files.txt.gz");
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(example.to_line_string().as_bytes()).unwrap();
+    std::fs::write(&path, encoder.finish().unwrap()).unwrap();
+
+    let path_str = path.to_str().unwrap();
+    let content = read_puzzle_file(path_str);
+    let parsed = parse_grid_info(path_str);
+    std::fs::remove_file(&path).ok();
+    assert_eq!(content.ok(), Some(example.to_line_string()), "The file should be decompressed.");
+    assert!(parsed.is_ok_and(|grid| grid.data == example.data), "Gzipped grids should go through the usual parsing.")
+}
+
+
+#[test]
+fn find_every_conflicting_cell() {
+    let mut grid = SudokuGrid::example_grid();
+    assert!(grid.conflicts().is_empty(), "The example grid follows the rules.");
+
+    // A 5 repeated in the first row, and a 9 repeated in the group of the bottom right corner
+    grid.set(2, 0, 5);
+    grid.set(6, 6, 9);
+    let mut conflicts = grid.conflicts();
+    conflicts.sort();
+    assert_eq!(conflicts, vec![(0, 0), (2, 0), (6, 6), (8, 8)])
+}
+
+
+/// Samurai Sudoku of `solve_samurai_sudoku()`, in the layout read by `SamuraiGrid::parse()`.
+const SAMURAI_PUZZLE: &str = "\
+24..9...1   ..12.6..9
+...1....2   .2.3.....
+.78...95.   86.1...3.
+.........   ...6.2.5.
+..1..8.6.   ......84.
+..6...59.   357..8692
+.8..124.6..95..9..47.
+.12.........6.38..9..
+6.4..9..5....7...4...
+      ....7...6
+      ....924.7
+      2..4..859
+..4...51893726..3....
+.59.....2...3..67.24.
+6..1..9....1...2....6
+36.7.....   1.7.....4
+.4..93...   .....1..7
+8...1..37   5..792318
+.239....5   ..2.1.873
+5..2.78..   ...58....
+.8....321   978.24.5.
+";
+
+#[test]
+fn solve_samurai_sudoku() {
+    let samurai = SamuraiGrid::parse(SAMURAI_PUZZLE).unwrap_or_else(|| panic!("The samurai should be parsed."));
+    assert_eq!(samurai.to_string(), SAMURAI_PUZZLE, "The layout should survive a round trip.");
+    // The bottom right group of the top left grid is the top left group of the center grid
+    assert_eq!(samurai.get(6, 6), 4);
+    assert_eq!((samurai.subgrids()[0].get(6, 6), samurai.subgrids()[2].get(0, 0)), (4, 4));
+
+    let solved = solve_samurai(samurai.clone(), MAX_ITERATIONS_DEFAULT).unwrap_or_else(|err| panic!("The samurai should be solved: {}", err));
+    assert!(solved.is_solved());
+    assert!(SamuraiGrid::cells().iter().all(|&(x, y)| samurai.get(x, y) == 0 || solved.get(x, y) == samurai.get(x, y)), "The clues should be kept.");
+    for (shared, (x, y)) in [(0, (6, 6)), (1, (12, 6)), (3, (6, 12)), (4, (12, 12))] {
+        let (left, top) = SAMURAI_OFFSETS[shared];
+        assert!((0..9).all(|i| solved.subgrids()[shared].get(x - left + i % 3, y - top + i / 3) == solved.subgrids()[2].get(x - 6 + i % 3, y - 6 + i / 3)), "The shared groups should match.");
+    }
+
+    // The 4 of the shared group is in the same column of the center grid
+    let mut invalid = samurai;
+    invalid.set(6, 9, 4);
+    assert!(matches!(solve_samurai(invalid, MAX_ITERATIONS_DEFAULT), Err(SudokuSolvingError::InvalidGrid(_))))
+}
+
+
+#[test]
+fn candidate_counts_display() {
+    let counts = SudokuGrid::example_grid().to_candidate_counts_string();
+    let rows: Vec<&str> = counts.lines().filter(|line| line.starts_with("| ")).collect();
+    assert_eq!(rows.len(), 9);
+    assert_eq!(rows[0], "| ##3 | 2#4 | 443 |");
+    // R5C5 can only hold a 5, R7C6 a 7, R7C9 a 4 and R8C8 a 3
+    assert_eq!(rows[4], "| #24 | #*# | 33# |");
+    assert_eq!(rows[6], "| 3#6 | 32* | ##* |");
+    assert_eq!(rows[7], "| 233 | ### | 2*# |");
+
+    let mut stuck = SudokuGrid::example_grid();
+    stuck.set(2, 0, 1);
+    stuck.set(2, 1, 2);
+    stuck.set(2, 2, 4);
+    assert!(stuck.to_candidate_counts_string().contains('!'), "A cell without candidates should stand out.");
+
+    // The empty cells of an empty hexadoku have 16 candidates, written in decimal
+    let hexadoku = SudokuGrid::empty_with_size(16).unwrap().to_candidate_counts_string();
+    let lines: Vec<&str> = hexadoku.lines().filter(|line| !line.is_empty()).collect();
+    assert_eq!(lines[1], format!("| {0} | {0} | {0} | {0} |", " 16".repeat(4)));
+    assert!(lines.iter().all(|line| line.len() == lines[0].len()), "The border should span the widened cells.")
+}
+
+
+#[test]
+fn pluggable_grid_recognizers() {
+    use recognition::{GridRecognizer, TranscriptRecognizer};
+    use std::path::Path;
+
+    // Any closure can stand in for a recognizer
+    let fixed = |_: &Path| Ok(SudokuGrid::example_grid());
+    let recognizers: Vec<Box<dyn GridRecognizer>> = vec![Box::new(fixed), Box::new(TranscriptRecognizer)];
+
+    let image = std::env::temp_dir().join("sudoku_solver_pluggable_grid_recognizers.png");
+    let transcript = image.with_extension("txt");
+    std::fs::write(&transcript, SudokuGrid::example_grid().to_sdk_string()).unwrap();
+    let recognized: Vec<Result<SudokuGrid, String>> = recognizers.iter().map(|recognizer| recognizer.recognize(&image)).collect();
+    std::fs::remove_file(&transcript).ok();
+    for grid in recognized {
+        assert!(grid.is_ok_and(|grid| grid.data == SudokuGrid::example_grid().data), "Both recognizers should read the example grid.");
+    }
+
+    let missing = TranscriptRecognizer.recognize(Path::new("sudoku_solver_missing_image.png"));
+    assert!(missing.is_err_and(|err| err.contains("sudoku_solver_missing_image.txt")), "The error should name the missing transcript.")
+}
+
+
+#[test]
+fn naked_singles_of_the_example_grid() {
+    let example = SudokuGrid::example_grid();
+    assert_eq!(example.naked_singles(), vec![(4, 4, 5), (5, 6, 7), (8, 6, 4), (7, 7, 3)]);
+
+    // Placing them keeps the grid on the way to its solution, and uncovers new ones
+    let solution = solve(example.clone(), MAX_ITERATIONS_DEFAULT).unwrap_or_else(|err| panic!("The example grid should be solved: {}", err));
+    let mut grid = example;
+    for (x, y, value) in grid.naked_singles() {
+        assert_eq!(solution.get(x, y), value);
+        grid.set(x, y, value)
+    }
+    assert!(!grid.naked_singles().is_empty());
+    assert!(solution.naked_singles().is_empty(), "A solved grid has no empty cell.")
+}
+
+
+#[test]
+fn unbounded_iterations_run_to_completion() {
+    // A cap of 0 lifts the cap rather than giving up right away
+    let example = SudokuGrid::example_grid();
+    assert!(solve(example.clone(), UNBOUNDED_ITERATIONS).is_ok_and(|grid| grid.is_solved()));
+    let cells: [u8; 81] = std::array::from_fn(|i| example.get(i % 9, i / 9));
+    assert!(solve_array(cells, UNBOUNDED_ITERATIONS).is_ok());
+
+    // This puzzle needs more than 10000 iterations of plain backtracking
+    let puzzle = parse_line_grid("..............3.85..1.2.......5.7.....4...1...9.......5......73..2.1........4...9").unwrap();
+    let solved = solve_with_options(puzzle, &SolveOptions::new(UNBOUNDED_ITERATIONS));
+    assert!(solved.is_ok_and(|grid| grid.is_solved()), "The puzzle should be solved without a cap.")
+}
+
+
+#[test]
+fn completeness_of_single_units() {
+    let solution = solve(SudokuGrid::example_grid(), MAX_ITERATIONS_DEFAULT).unwrap_or_else(|err| panic!("The example grid should be solved: {}", err));
+    let mut grid = solution.clone();
+    // Emptying R2C2 leaves its row, column and group incomplete, and a repeated digit is no better
+    grid.set(1, 1, 0);
+    grid.set(7, 7, solution.get(8, 7));
+
+    assert!(grid.is_row_complete(0) && grid.is_column_complete(0) && grid.is_box_complete(4, 4));
+    assert!(!grid.is_row_complete(1) && !grid.is_column_complete(1) && !grid.is_box_complete(2, 2));
+    assert!(!grid.is_row_complete(7) && !grid.is_column_complete(7) && !grid.is_box_complete(8, 8));
+    assert!(grid.is_box_complete(8, 0), "The group of the top right corner is untouched.")
+}
+
+
+#[test]
+fn diff_two_grids() {
+    let example = SudokuGrid::example_grid();
+    let solution = solve(example.clone(), MAX_ITERATIONS_DEFAULT).unwrap_or_else(|err| panic!("The example grid should be solved: {}", err));
+    assert_eq!(solution.diff(&solution.clone()), vec![]);
+
+    let mut changed = solution.clone();
+    changed.set(0, 0, 0);
+    changed.set(8, 4, 2);
+    changed.set(3, 8, 9);
+    assert_eq!(solution.diff(&changed), vec![(0, 0, 5, 0), (8, 4, 1, 2), (3, 8, 2, 9)]);
+    assert_eq!(changed.diff(&solution), vec![(0, 0, 0, 5), (8, 4, 2, 1), (3, 8, 9, 2)], "Swapping the grids swaps the values.");
+
+    // The cells filled by the solver are the ones the puzzle leaves empty
+    assert_eq!(example.diff(&solution).len(), example.empty_count())
+}
@@ -0,0 +1,40 @@
+//! WebAssembly entry points of the solver, enabled by the `wasm` feature.
+//!
+//! Grids go in and out in the 81-character line format, and errors are returned as strings starting with `error:` rather than thrown,
+//! so that the functions are easy to call from JavaScript. Nothing here touches the file system or the standard input.
+//! The module can be built with `cargo rustc --lib --release --target wasm32-unknown-unknown --features wasm --crate-type cdylib`, then bound with `wasm-bindgen`.
+
+use rand::thread_rng;
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{Difficulty, GENERATION_ATTEMPTS_DEFAULT, MAX_ITERATIONS_DEFAULT, SudokuGrid, Symmetry, parse_grid, solve};
+
+/// Solves a puzzle given in the 81-character line format (see `parse_grid()` for the accepted characters) and returns its solution in the same format.
+#[wasm_bindgen]
+pub fn solve_line(input: &str) -> String {
+    match parse_grid(input) {
+        Ok(grid) => match solve(grid, MAX_ITERATIONS_DEFAULT) {
+            Ok(solved_grid) => solved_grid.to_line_string(),
+            Err(err) => format!("error: {}", err)
+        },
+        Err(err) => format!("error: {}", err)
+    }
+}
+
+/// Generates a puzzle of the specified difficulty ('easy', 'medium', 'hard' or 'expert') and returns it in the 81-character line format.
+#[wasm_bindgen]
+pub fn generate_line(difficulty: &str) -> String {
+    let difficulty = match difficulty {
+        "easy" => Difficulty::Easy,
+        "medium" => Difficulty::Medium,
+        "hard" => Difficulty::Hard,
+        "expert" => Difficulty::Expert,
+        other => return format!("error: unknown difficulty '{}', expected 'easy', 'medium', 'hard' or 'expert'", other)
+    };
+
+    // The generation runs on the main thread of the page, so it can't be left to spin
+    match SudokuGrid::try_generate_symmetric(difficulty, Symmetry::None, &mut thread_rng(), GENERATION_ATTEMPTS_DEFAULT) {
+        Ok(puzzle) => puzzle.to_line_string(),
+        Err(err) => format!("error: {}", err)
+    }
+}
@@ -0,0 +1,31 @@
+//! Runs the program itself, to check how its arguments combine.
+
+use std::process::{Command, Output};
+
+/// Runs the program with the specified arguments and returns its output.
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_sudoku_solver"))
+        .args(args)
+        .output()
+        .unwrap_or_else(|err| panic!("Couldn't run the program: {}", err))
+}
+
+
+#[test]
+fn json_full_report_with_timeout() {
+    let output = run(&["--grid", "easy1", "--json-full", "--timeout", "5"]);
+    assert!(output.status.success(), "The report should accept a timeout: {}", String::from_utf8_lossy(&output.stderr));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap_or_else(|err| panic!("The report should be JSON: {}", err));
+    assert_eq!(report["unique"], serde_json::json!(true), "The easy puzzle has a single solution.")
+}
+
+
+#[test]
+fn timeout_only_rejects_dlx() {
+    let output = run(&["--grid", "easy1", "--solver", "backtrack", "--timeout", "5", "--quiet"]);
+    assert!(output.status.success(), "The backtracking solver honours the timeout: {}", String::from_utf8_lossy(&output.stderr));
+
+    let output = run(&["--grid", "easy1", "--solver", "dlx", "--timeout", "5"]);
+    assert_eq!(output.status.code(), Some(1), "Algorithm X has no deadline.");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--timeout"), "The error should name the offending argument.")
+}